@@ -3,6 +3,8 @@ pub mod bodies;
 pub mod header;
 pub mod parallel;
 pub mod pdu;
+pub mod session;
+pub mod writer;
 
 pub use bodies::{
     CleanupSetPdu, ClosePdu, CloseReason, CommitSetPdu, GetBulkPdu, GetPdu, NotifyPdu, OpenPdu,
@@ -14,6 +16,8 @@ pub use parallel::{
     encode_varbinds_batch,
 };
 pub use pdu::{SearchRange, ValueType, VarBind, decode_value, encode_value};
+pub use session::{SessionConfig, SessionError, SyncSession};
+pub use writer::PduWriter;
 
 #[cfg(test)]
 mod tests {
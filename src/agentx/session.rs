@@ -0,0 +1,572 @@
+//! AgentX subagent session management.
+//!
+//! The `bindings`/`pdu` layers only know how to encode and decode individual
+//! PDUs; something still has to own the socket to the master agent, assign
+//! `session_id`/`transaction_id`/`packet_id`, perform the `Open` handshake,
+//! and match replies to outstanding requests. [`SyncSession`] and
+//! [`AsyncSession`] do that, sharing the bookkeeping defined by `SessionState`.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::oid::Oid;
+
+use super::bodies::{
+    ClosePdu, CloseReason, GetBulkPdu, NotifyPdu, OpenPdu, PingPdu, RegisterPdu, ResponseError,
+    ResponsePdu,
+};
+use super::header::{Flags, HEADER_SIZE, Header, PduType};
+use super::pdu::VarBind;
+
+/// Errors that can arise while driving an AgentX session.
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    Protocol(String),
+    /// The master agent's Response-PDU carried a non-zero `error`, i.e. it
+    /// rejected the request (e.g. a failed `Register` or `Open`).
+    Rejected { error: ResponseError, index: u16 },
+    /// The master agent never replied within `retry_interval` on any of the
+    /// `max_retries + 1` attempts.
+    Timeout,
+    /// The session hasn't completed the `Open` handshake yet.
+    NotOpen,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Io(e) => write!(f, "I/O error: {e}"),
+            SessionError::Protocol(s) => write!(f, "protocol error: {s}"),
+            SessionError::Rejected { error, index } => write!(
+                f,
+                "master agent rejected request: error {} (index {index})",
+                *error as u16
+            ),
+            SessionError::Timeout => write!(f, "timed out waiting for master agent reply"),
+            SessionError::NotOpen => write!(f, "session is not open"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(e: io::Error) -> Self {
+        SessionError::Io(e)
+    }
+}
+
+/// Connection parameters shared by [`SyncSession`] and [`AsyncSession`].
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// Subtree this subagent wants to register, e.g. an enterprise MIB root.
+    pub subtree: Oid,
+    /// Description passed in the `Open` PDU.
+    pub description: String,
+    /// Master-agent timeout hint (seconds), sent in the `Open` PDU. Our own
+    /// retries are paced by `retry_interval` instead.
+    pub timeout: u8,
+    /// Number of retransmissions before [`SessionError::Timeout`].
+    pub max_retries: u32,
+    /// How long to wait for a reply before retransmitting.
+    pub retry_interval: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            subtree: Oid::new(vec![1]).expect("non-empty"),
+            description: String::new(),
+            timeout: 5,
+            max_retries: 3,
+            retry_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Bookkeeping shared by both session flavors: the master-assigned
+/// `session_id`, a monotonically increasing `transaction_id` (one per
+/// logical request - `register`/`notify`/`ping`/`close`), and a
+/// monotonically increasing `packet_id` (one per outgoing request PDU; a
+/// retransmit resends the same packet under the same `packet_id`).
+trait SessionState {
+    fn session_id(&self) -> u32;
+    fn next_transaction_id(&self) -> u32;
+    fn next_packet_id(&self) -> u32;
+}
+
+struct SharedState {
+    session_id: AtomicU32,
+    transaction_id: AtomicU32,
+    packet_id: AtomicU32,
+}
+
+impl SharedState {
+    fn new() -> Self {
+        Self {
+            session_id: AtomicU32::new(0),
+            transaction_id: AtomicU32::new(0),
+            packet_id: AtomicU32::new(0),
+        }
+    }
+}
+
+impl SessionState for SharedState {
+    fn session_id(&self) -> u32 {
+        self.session_id.load(Ordering::SeqCst)
+    }
+
+    fn next_transaction_id(&self) -> u32 {
+        self.transaction_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn next_packet_id(&self) -> u32 {
+        self.packet_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+fn encode_full_pdu(header: Header, body: &[u8]) -> Vec<u8> {
+    let header = header.with_payload_length(body.len() as u32);
+    let mut buf = Vec::with_capacity(HEADER_SIZE + body.len());
+    header.encode(&mut buf).expect("encoding to a Vec cannot fail");
+    buf.extend_from_slice(body);
+    buf
+}
+
+/// Transport-agnostic socket: either a Unix-domain or TCP stream to the
+/// master agent.
+enum Transport {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Read for Transport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Transport::Unix(s) => s.read(buf),
+            Transport::Tcp(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Transport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Transport::Unix(s) => s.write(buf),
+            Transport::Tcp(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Transport::Unix(s) => s.flush(),
+            Transport::Tcp(s) => s.flush(),
+        }
+    }
+}
+
+impl Transport {
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Transport::Unix(s) => s.set_read_timeout(dur),
+            Transport::Tcp(s) => s.set_read_timeout(dur),
+        }
+    }
+}
+
+/// Blocking AgentX subagent session.
+///
+/// Owns the socket to the master agent. `request` sends a PDU and blocks
+/// until a reply with a matching `packet_id` arrives, retransmitting up to
+/// `config.max_retries` times.
+pub struct SyncSession {
+    transport: Transport,
+    state: SharedState,
+    config: SessionConfig,
+}
+
+impl SyncSession {
+    /// Connects over TCP and performs the `Open` handshake.
+    pub fn connect_tcp(addr: impl std::net::ToSocketAddrs, config: SessionConfig) -> Result<Self, SessionError> {
+        let stream = TcpStream::connect(addr)?;
+        Self::open(Transport::Tcp(stream), config)
+    }
+
+    /// Connects over a Unix-domain socket and performs the `Open` handshake.
+    #[cfg(unix)]
+    pub fn connect_unix(path: impl AsRef<std::path::Path>, config: SessionConfig) -> Result<Self, SessionError> {
+        let stream = UnixStream::connect(path)?;
+        Self::open(Transport::Unix(stream), config)
+    }
+
+    fn open(transport: Transport, config: SessionConfig) -> Result<Self, SessionError> {
+        let mut session = Self {
+            transport,
+            state: SharedState::new(),
+            config,
+        };
+
+        let pdu = OpenPdu::new(
+            session.config.timeout,
+            session.config.subtree.clone(),
+            session.config.description.as_bytes().to_vec(),
+        );
+        let mut body = Vec::new();
+        pdu.encode(&mut body)
+            .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+        let header = Header::new(PduType::Open, 0, 0, session.state.next_packet_id());
+        let response = session.request(header, &body)?;
+        session
+            .state
+            .session_id
+            .store(response.session_id, Ordering::SeqCst);
+        Ok(session)
+    }
+
+    /// Registers `subtree` with the master agent.
+    pub fn register(
+        &mut self,
+        subtree: &Oid,
+        priority: u8,
+        timeout: u8,
+        context: Option<&str>,
+    ) -> Result<(), SessionError> {
+        let pdu = RegisterPdu::new(subtree.clone(), priority, timeout);
+        let mut body = Vec::new();
+        if let Some(ctx) = context {
+            super::pdu::encode_octet_string(&mut body, ctx.as_bytes())
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+        }
+        pdu.encode(&mut body)
+            .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+        let mut flags = Flags::NETWORK_BYTE_ORDER;
+        if context.is_some() {
+            flags |= Flags::NON_DEFAULT_CONTEXT;
+        }
+
+        let header = self.next_header(PduType::Register).with_flags(flags);
+        self.request(header, &body)?;
+        Ok(())
+    }
+
+    /// Sends a `Notify` PDU (trap) containing `varbinds`.
+    pub fn notify(&mut self, varbinds: Vec<VarBind>, context: Option<&str>) -> Result<(), SessionError> {
+        let pdu = NotifyPdu::new(varbinds);
+        let mut body = Vec::new();
+        if let Some(ctx) = context {
+            super::pdu::encode_octet_string(&mut body, ctx.as_bytes())
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+        }
+        pdu.encode(&mut body)
+            .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+        let mut flags = Flags::NETWORK_BYTE_ORDER;
+        if context.is_some() {
+            flags |= Flags::NON_DEFAULT_CONTEXT;
+        }
+
+        let header = self.next_header(PduType::Notify).with_flags(flags);
+        self.request(header, &body)?;
+        Ok(())
+    }
+
+    /// Sends a `Ping` PDU to check liveness of the master agent.
+    pub fn ping(&mut self) -> Result<(), SessionError> {
+        let pdu = PingPdu::new();
+        let mut body = Vec::new();
+        pdu.encode(&mut body)
+            .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+        let header = self.next_header(PduType::Ping);
+        self.request(header, &body)?;
+        Ok(())
+    }
+
+    /// Closes the session, notifying the master agent of `reason`.
+    pub fn close(mut self, reason: CloseReason) -> Result<(), SessionError> {
+        let pdu = ClosePdu::new(reason);
+        let mut body = Vec::new();
+        pdu.encode(&mut body)
+            .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+        let header = self.next_header(PduType::Close);
+        self.request(header, &body)?;
+        Ok(())
+    }
+
+    fn next_header(&self, pdu_type: PduType) -> Header {
+        Header::new(
+            pdu_type,
+            self.state.session_id(),
+            self.state.next_transaction_id(),
+            self.state.next_packet_id(),
+        )
+    }
+
+    /// Sends `body` under `header` and blocks until a reply with a matching
+    /// `packet_id` is read, retransmitting on timeout.
+    fn request(&mut self, header: Header, body: &[u8]) -> Result<RawResponse, SessionError> {
+        let packet = encode_full_pdu(header.clone(), body);
+
+        for attempt in 0..=self.config.max_retries {
+            self.transport.write_all(&packet)?;
+            self.transport
+                .set_read_timeout(Some(self.config.retry_interval))?;
+
+            match self.read_response(header.packet_id) {
+                Ok(response) => return Ok(response),
+                Err(SessionError::Io(e))
+                    if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) =>
+                {
+                    if attempt == self.config.max_retries {
+                        return Err(SessionError::Timeout);
+                    }
+                }
+                Err(SessionError::Timeout) => {
+                    if attempt == self.config.max_retries {
+                        return Err(SessionError::Timeout);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(SessionError::Timeout)
+    }
+
+    fn read_response(&mut self, expected_packet_id: u32) -> Result<RawResponse, SessionError> {
+        let deadline = Instant::now() + self.config.retry_interval;
+
+        loop {
+            let mut header_buf = [0u8; HEADER_SIZE];
+            self.transport.read_exact(&mut header_buf)?;
+            let mut cursor = std::io::Cursor::new(&header_buf[..]);
+            let header = Header::decode(&mut cursor)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let mut payload = vec![0u8; header.payload_length as usize];
+            self.transport.read_exact(&mut payload)?;
+
+            if header.packet_id == expected_packet_id {
+                let mut payload_cursor = std::io::Cursor::new(&payload[..]);
+                let response = ResponsePdu::decode(&mut payload_cursor)
+                    .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+                if response.error as u16 != 0 {
+                    return Err(SessionError::Rejected {
+                        error: response.error,
+                        index: response.index,
+                    });
+                }
+
+                return Ok(RawResponse {
+                    session_id: header.session_id,
+                });
+            }
+
+            if Instant::now() >= deadline {
+                return Err(SessionError::Timeout);
+            }
+        }
+    }
+}
+
+struct RawResponse {
+    session_id: u32,
+}
+
+/// Non-blocking counterpart to [`SyncSession`]: every method writes its PDU
+/// and returns without waiting for (or matching) a reply, leaving the
+/// caller to read responses off the socket separately.
+#[cfg(feature = "async")]
+pub mod async_session {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream as AsyncTcpStream;
+    #[cfg(unix)]
+    use tokio::net::UnixStream as AsyncUnixStream;
+
+    enum AsyncTransport {
+        #[cfg(unix)]
+        Unix(AsyncUnixStream),
+        Tcp(AsyncTcpStream),
+    }
+
+    impl AsyncTransport {
+        async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+            match self {
+                #[cfg(unix)]
+                AsyncTransport::Unix(s) => s.write_all(buf).await,
+                AsyncTransport::Tcp(s) => s.write_all(buf).await,
+            }
+        }
+
+        async fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+            match self {
+                #[cfg(unix)]
+                AsyncTransport::Unix(s) => {
+                    s.read_exact(buf).await?;
+                }
+                AsyncTransport::Tcp(s) => {
+                    s.read_exact(buf).await?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Async AgentX subagent session. Fire-and-forget: `register`/`notify`/
+    /// `ping`/`close` write their PDU and return once the write completes.
+    pub struct AsyncSession {
+        transport: AsyncTransport,
+        state: SharedState,
+        config: SessionConfig,
+    }
+
+    impl AsyncSession {
+        pub async fn connect_tcp(
+            addr: impl tokio::net::ToSocketAddrs,
+            config: SessionConfig,
+        ) -> Result<Self, SessionError> {
+            let stream = AsyncTcpStream::connect(addr).await?;
+            Self::open(AsyncTransport::Tcp(stream), config).await
+        }
+
+        #[cfg(unix)]
+        pub async fn connect_unix(
+            path: impl AsRef<std::path::Path>,
+            config: SessionConfig,
+        ) -> Result<Self, SessionError> {
+            let stream = AsyncUnixStream::connect(path).await?;
+            Self::open(AsyncTransport::Unix(stream), config).await
+        }
+
+        async fn open(transport: AsyncTransport, config: SessionConfig) -> Result<Self, SessionError> {
+            let mut session = Self {
+                transport,
+                state: SharedState::new(),
+                config,
+            };
+
+            let pdu = OpenPdu::new(
+                session.config.timeout,
+                session.config.subtree.clone(),
+                session.config.description.as_bytes().to_vec(),
+            );
+            let mut body = Vec::new();
+            pdu.encode(&mut body)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let header = Header::new(PduType::Open, 0, 0, session.state.next_packet_id());
+            session.fire(header, &body).await?;
+            // The caller is responsible for reading the Open response off
+            // the socket and supplying the assigned session_id via
+            // `set_session_id`, since this session never blocks for replies.
+            Ok(session)
+        }
+
+        /// Records the master-assigned `session_id` once the caller has read
+        /// the `Open` response off the socket.
+        pub fn set_session_id(&mut self, session_id: u32) {
+            self.state.session_id.store(session_id, Ordering::SeqCst);
+        }
+
+        pub async fn register(
+            &mut self,
+            subtree: &Oid,
+            priority: u8,
+            timeout: u8,
+            context: Option<&str>,
+        ) -> Result<(), SessionError> {
+            let pdu = RegisterPdu::new(subtree.clone(), priority, timeout);
+            let mut body = Vec::new();
+            if let Some(ctx) = context {
+                super::super::pdu::encode_octet_string(&mut body, ctx.as_bytes())
+                    .map_err(|e| SessionError::Protocol(e.to_string()))?;
+            }
+            pdu.encode(&mut body)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let mut flags = Flags::NETWORK_BYTE_ORDER;
+            if context.is_some() {
+                flags |= Flags::NON_DEFAULT_CONTEXT;
+            }
+
+            let header = self.next_header(PduType::Register).with_flags(flags);
+            self.fire(header, &body).await
+        }
+
+        pub async fn notify(
+            &mut self,
+            varbinds: Vec<VarBind>,
+            context: Option<&str>,
+        ) -> Result<(), SessionError> {
+            let pdu = NotifyPdu::new(varbinds);
+            let mut body = Vec::new();
+            if let Some(ctx) = context {
+                super::super::pdu::encode_octet_string(&mut body, ctx.as_bytes())
+                    .map_err(|e| SessionError::Protocol(e.to_string()))?;
+            }
+            pdu.encode(&mut body)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let mut flags = Flags::NETWORK_BYTE_ORDER;
+            if context.is_some() {
+                flags |= Flags::NON_DEFAULT_CONTEXT;
+            }
+
+            let header = self.next_header(PduType::Notify).with_flags(flags);
+            self.fire(header, &body).await
+        }
+
+        pub async fn ping(&mut self) -> Result<(), SessionError> {
+            let pdu = PingPdu::new();
+            let mut body = Vec::new();
+            pdu.encode(&mut body)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let header = self.next_header(PduType::Ping);
+            self.fire(header, &body).await
+        }
+
+        pub async fn close(mut self, reason: CloseReason) -> Result<(), SessionError> {
+            let pdu = ClosePdu::new(reason);
+            let mut body = Vec::new();
+            pdu.encode(&mut body)
+                .map_err(|e| SessionError::Protocol(e.to_string()))?;
+
+            let header = self.next_header(PduType::Close);
+            self.fire(header, &body).await
+        }
+
+        fn next_header(&self, pdu_type: PduType) -> Header {
+            Header::new(
+                pdu_type,
+                self.state.session_id(),
+                self.state.next_transaction_id(),
+                self.state.next_packet_id(),
+            )
+        }
+
+        async fn fire(&mut self, header: Header, body: &[u8]) -> Result<(), SessionError> {
+            let packet = encode_full_pdu(header, body);
+            self.transport.write_all(&packet).await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_session::AsyncSession;
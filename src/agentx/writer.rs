@@ -0,0 +1,111 @@
+//! Batched, Nagle-free PDU writer for the AgentX transport.
+//!
+//! A subagent servicing a GetBulk walk emits many small `Response` PDUs in
+//! quick succession; left to TCP's default buffering, Nagle's algorithm
+//! delays each small write waiting for an ACK, adding latency per PDU.
+//! [`PduWriter`] sets `TCP_NODELAY` on the connection and coalesces a
+//! processing cycle's worth of encoded PDUs into one buffered write.
+
+use std::io::{self, Write};
+use std::net::TcpStream;
+
+/// Wraps a TCP connection to the master agent, disabling Nagle's algorithm
+/// and batching queued PDU bytes into a single `write` per [`flush`](Self::flush).
+pub struct PduWriter<W: Write> {
+    sink: W,
+    pending: Vec<u8>,
+}
+
+impl PduWriter<TcpStream> {
+    /// Wraps `stream`, setting `TCP_NODELAY` so queued PDUs go out
+    /// immediately on [`flush`](Self::flush) instead of waiting for Nagle's
+    /// coalescing window.
+    pub fn new_tcp(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            sink: stream,
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl<W: Write> PduWriter<W> {
+    /// Wraps an already-configured sink without touching socket options.
+    /// Used in tests and for non-TCP transports.
+    pub fn wrap(sink: W) -> Self {
+        Self {
+            sink,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Appends an already-encoded PDU (e.g. from `encode_full_pdu`) to the
+    /// pending batch without writing it to the socket yet.
+    pub fn queue(&mut self, pdu_bytes: &[u8]) {
+        self.pending.extend_from_slice(pdu_bytes);
+    }
+
+    /// Returns the number of bytes currently queued but not yet flushed.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Writes all queued PDUs to the underlying sink in a single `write`
+    /// call and clears the queue.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.sink.write_all(&self.pending)?;
+        self.sink.flush()?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for PduWriter<W> {
+    /// Best-effort flush so a batch isn't silently dropped if the caller
+    /// forgets to flush at the end of a processing cycle.
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_coalesces_into_one_write() {
+        let mut out = Vec::new();
+        {
+            let mut writer = PduWriter::wrap(&mut out);
+            writer.queue(&[1, 2, 3]);
+            writer.queue(&[4, 5]);
+            assert_eq!(writer.pending_len(), 5);
+            writer.flush().unwrap();
+            assert_eq!(writer.pending_len(), 0);
+        }
+        assert_eq!(out, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_auto_flush_on_drop() {
+        let mut out = Vec::new();
+        {
+            let mut writer = PduWriter::wrap(&mut out);
+            writer.queue(&[9, 9]);
+        }
+        assert_eq!(out, vec![9, 9]);
+    }
+
+    #[test]
+    fn test_empty_flush_is_a_noop() {
+        let mut out = Vec::new();
+        {
+            let mut writer = PduWriter::wrap(&mut out);
+            writer.flush().unwrap();
+        }
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Shared BER/ASN.1 length and TLV framing helpers.
+//!
+//! `types`, `usm`, and `trap` each parse untrusted bytes (a captured
+//! packet, a UDP datagram straight off the wire) into their own PDU
+//! shapes, and each used to carry its own copy of this length/TLV
+//! framing code. Centralizing it here means the one invariant that
+//! actually matters for untrusted input — never allocate a buffer sized
+//! from a length field before confirming the input can actually back it
+//! — only has to be enforced in one place.
+
+use std::io::{Cursor, Read};
+
+/// Errors framing or de-framing a BER TLV. Callers generally wrap this in
+/// their own error type via `.map_err`, since each has its own preferred
+/// variant and message style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FramingError {
+    UnexpectedEof,
+    InvalidLength,
+    LengthExceedsRemainingInput { claimed: usize, remaining: usize },
+}
+
+impl std::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramingError::UnexpectedEof => write!(f, "unexpected end of BER input"),
+            FramingError::InvalidLength => write!(f, "invalid BER length encoding"),
+            FramingError::LengthExceedsRemainingInput { claimed, remaining } => write!(
+                f,
+                "BER length {claimed} exceeds the {remaining} bytes remaining in the input"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FramingError {}
+
+/// Encodes a BER length in definite short or long form.
+pub(crate) fn encode_length(buf: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        buf.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let significant = &len_bytes[len_bytes.iter().position(|&b| b != 0).unwrap_or(7)..];
+        buf.push(0x80 | significant.len() as u8);
+        buf.extend_from_slice(significant);
+    }
+}
+
+/// Decodes a BER length in definite short or long form. Does not, by
+/// itself, check the decoded value against the bytes remaining in
+/// `cursor` — callers that go on to allocate a buffer of this size
+/// should use [`read_exact_bytes`] or [`read_tlv`], which do that check
+/// before allocating.
+pub(crate) fn decode_length(cursor: &mut Cursor<&[u8]>) -> Result<usize, FramingError> {
+    let mut byte = [0u8; 1];
+    cursor
+        .read_exact(&mut byte)
+        .map_err(|_| FramingError::UnexpectedEof)?;
+
+    if byte[0] & 0x80 == 0 {
+        Ok(byte[0] as usize)
+    } else {
+        let num_bytes = (byte[0] & 0x7f) as usize;
+        if num_bytes == 0 || num_bytes > std::mem::size_of::<usize>() {
+            return Err(FramingError::InvalidLength);
+        }
+        let mut len_bytes = vec![0u8; num_bytes];
+        cursor
+            .read_exact(&mut len_bytes)
+            .map_err(|_| FramingError::UnexpectedEof)?;
+        Ok(len_bytes
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize))
+    }
+}
+
+/// Reads exactly `len` bytes from `cursor`, first checking that many
+/// bytes actually remain so a bogus length read from untrusted input
+/// can't trigger an unbounded allocation.
+pub(crate) fn read_exact_bytes(
+    cursor: &mut Cursor<&[u8]>,
+    len: usize,
+) -> Result<Vec<u8>, FramingError> {
+    let remaining = cursor.get_ref().len() - cursor.position() as usize;
+    if len > remaining {
+        return Err(FramingError::LengthExceedsRemainingInput {
+            claimed: len,
+            remaining,
+        });
+    }
+    let mut buf = vec![0u8; len];
+    cursor
+        .read_exact(&mut buf)
+        .map_err(|_| FramingError::UnexpectedEof)?;
+    Ok(buf)
+}
+
+/// Reads a full BER TLV (tag, length, content) from `cursor`, returning
+/// the tag byte and content bytes. The length is bounds-checked against
+/// the bytes remaining in `cursor` before any allocation.
+pub(crate) fn read_tlv(cursor: &mut Cursor<&[u8]>) -> Result<(u8, Vec<u8>), FramingError> {
+    let mut tag_byte = [0u8; 1];
+    cursor
+        .read_exact(&mut tag_byte)
+        .map_err(|_| FramingError::UnexpectedEof)?;
+    let len = decode_length(cursor)?;
+    let content = read_exact_bytes(cursor, len)?;
+    Ok((tag_byte[0], content))
+}
+
+/// Encodes a full BER TLV (tag, length, content) into `buf`.
+pub(crate) fn encode_tlv(buf: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    buf.push(tag);
+    encode_length(buf, content.len());
+    buf.extend_from_slice(content);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_roundtrip_short_and_long_form() {
+        for len in [0usize, 1, 127, 128, 255, 65536] {
+            let mut buf = Vec::new();
+            encode_length(&mut buf, len);
+            let mut cursor = Cursor::new(buf.as_slice());
+            assert_eq!(decode_length(&mut cursor).unwrap(), len);
+        }
+    }
+
+    #[test]
+    fn test_read_tlv_roundtrip() {
+        let mut buf = Vec::new();
+        encode_tlv(&mut buf, 0x04, b"hello");
+        let mut cursor = Cursor::new(buf.as_slice());
+        let (tag, content) = read_tlv(&mut cursor).unwrap();
+        assert_eq!(tag, 0x04);
+        assert_eq!(content, b"hello");
+    }
+
+    #[test]
+    fn test_read_tlv_rejects_length_exceeding_remaining_input() {
+        // Tag 0x04 (OCTET STRING), long-form length claiming 0xFFFFFFFF
+        // bytes, with nothing actually backing it.
+        let bytes = [0x04u8, 0x84, 0xff, 0xff, 0xff, 0xff];
+        let mut cursor = Cursor::new(&bytes[..]);
+        assert!(matches!(
+            read_tlv(&mut cursor),
+            Err(FramingError::LengthExceedsRemainingInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_exact_bytes_rejects_truncated_input() {
+        let bytes = [0x01u8, 0x02];
+        let mut cursor = Cursor::new(&bytes[..]);
+        assert!(matches!(
+            read_exact_bytes(&mut cursor, 10),
+            Err(FramingError::LengthExceedsRemainingInput { .. })
+        ));
+    }
+}
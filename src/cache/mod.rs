@@ -0,0 +1,593 @@
+//! Persistent on-disk cache of polled OID values, so a long-running poller
+//! can survive a restart without re-walking every device from scratch.
+//!
+//! [`OidCache`] is an append-only log keyed by the OID's BER encoding (see
+//! [`crate::oid::Oid::to_ber`]): each [`OidCache::put`] appends a record
+//! carrying the serialized [`Value`], a timestamp, and a CRC32 of the
+//! record's contents, so [`OidCache::open`] can detect a torn or corrupted
+//! trailing record (e.g. from a crash mid-write) and simply stop replaying
+//! instead of panicking. [`OidCache::compact`] rewrites the file holding
+//! only the current, live entries, dropping superseded and deleted keys.
+//!
+//! This module is only built with the `cache` feature enabled, so the core
+//! crate doesn't pay for a file format and on-disk index it may never use.
+//! With the `cache-encryption` feature also enabled, [`OidCache::open_encrypted`]
+//! transparently AES-128-CFB-encrypts every record, for callers whose
+//! cached values are adjacent to credentials (e.g. community strings
+//! embedded in vendor-specific OIDs).
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::oid::{Oid, OidError};
+use crate::types::{BerError, Value};
+
+#[cfg(feature = "cache-encryption")]
+use cfb_mode::cipher::{AsyncStreamCipher, KeyIvInit};
+
+#[cfg(feature = "cache-encryption")]
+type Aes128CfbEnc = cfb_mode::Encryptor<aes::Aes128>;
+#[cfg(feature = "cache-encryption")]
+type Aes128CfbDec = cfb_mode::Decryptor<aes::Aes128>;
+
+/// Errors reading or writing the cache file. Corrupt individual records
+/// encountered while replaying the log are not surfaced here; they're
+/// silently skipped, see [`OidCache::open`].
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    Oid(OidError),
+    Ber(BerError),
+}
+
+impl std::fmt::Display for CacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheError::Io(e) => write!(f, "cache I/O error: {e}"),
+            CacheError::Oid(e) => write!(f, "cache key is not a valid OID: {e}"),
+            CacheError::Ber(e) => write!(f, "cache value is not valid BER: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Io(e) => Some(e),
+            CacheError::Oid(e) => Some(e),
+            CacheError::Ber(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(e: io::Error) -> Self {
+        CacheError::Io(e)
+    }
+}
+
+impl From<OidError> for CacheError {
+    fn from(e: OidError) -> Self {
+        CacheError::Oid(e)
+    }
+}
+
+impl From<BerError> for CacheError {
+    fn from(e: BerError) -> Self {
+        CacheError::Ber(e)
+    }
+}
+
+/// A persistent, append-only cache of the last-known [`Value`] for each
+/// [`Oid`] a poller has seen, plus when it was last written.
+pub struct OidCache {
+    path: PathBuf,
+    entries: HashMap<Oid, (Value, SystemTime)>,
+    key: Option<[u8; 16]>,
+    next_nonce: u64,
+}
+
+impl OidCache {
+    /// Opens (creating if absent) a plaintext cache file at `path`,
+    /// replaying its existing records into memory.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CacheError> {
+        Self::open_with_key(path, None)
+    }
+
+    /// Opens (creating if absent) a cache file at `path` whose records are
+    /// transparently AES-128-CFB-encrypted with `key`.
+    #[cfg(feature = "cache-encryption")]
+    pub fn open_encrypted(path: impl AsRef<Path>, key: [u8; 16]) -> Result<Self, CacheError> {
+        Self::open_with_key(path, Some(key))
+    }
+
+    fn open_with_key(path: impl AsRef<Path>, key: Option<[u8; 16]>) -> Result<Self, CacheError> {
+        let path = path.as_ref().to_path_buf();
+        let mut cache = Self {
+            path: path.clone(),
+            entries: HashMap::new(),
+            key,
+            next_nonce: 0,
+        };
+
+        if path.exists() {
+            let bytes = fs::read(&path)?;
+            cache.replay(&bytes);
+        }
+        Ok(cache)
+    }
+
+    /// Replays every well-formed record in `bytes` into `entries`, applying
+    /// them in file order so a later `put` (or tombstone) for the same key
+    /// wins. Stops at a truncated (torn) trailing record, since that's
+    /// exactly what a crash mid-append leaves behind, and skips any record
+    /// whose CRC or contents don't decode; neither case is an error.
+    ///
+    /// Also restores `next_nonce` from the highest nonce seen in an
+    /// encrypted record, so a reopened cache resumes the nonce sequence
+    /// instead of reusing IVs already used with the same key (see
+    /// [`extract_nonce`]).
+    fn replay(&mut self, bytes: &[u8]) {
+        let mut cursor = 0usize;
+        let mut max_nonce: Option<u64> = None;
+        while cursor + 4 <= bytes.len() {
+            let frame_len =
+                u32::from_be_bytes(bytes[cursor..cursor + 4].try_into().unwrap()) as usize;
+            cursor += 4;
+            if cursor + frame_len > bytes.len() {
+                break; // Truncated trailing record; nothing more to replay.
+            }
+            let framed = &bytes[cursor..cursor + frame_len];
+            cursor += frame_len;
+
+            if let Some(nonce) = extract_nonce(&self.key, framed) {
+                max_nonce = Some(max_nonce.map_or(nonce, |m| m.max(nonce)));
+            }
+
+            let Ok(plaintext) = decode_frame(&self.key, framed) else {
+                continue;
+            };
+            let Ok((oid, value, timestamp)) = decode_record(&plaintext) else {
+                continue;
+            };
+            match value {
+                Some(value) => {
+                    self.entries.insert(oid, (value, timestamp));
+                }
+                None => {
+                    self.entries.remove(&oid);
+                }
+            }
+        }
+        if let Some(max_nonce) = max_nonce {
+            self.next_nonce = max_nonce + 1;
+        }
+    }
+
+    /// The cached value for `oid` and when it was written, or `None` if
+    /// nothing is cached for it.
+    pub fn get(&self, oid: &Oid) -> Option<(Value, SystemTime)> {
+        self.entries.get(oid).cloned()
+    }
+
+    /// Records `value` for `oid`, timestamped now, both in memory and as a
+    /// newly appended record on disk.
+    pub fn put(&mut self, oid: &Oid, value: &Value) -> Result<(), CacheError> {
+        let timestamp = SystemTime::now();
+        self.append_record(oid, Some(value), timestamp)?;
+        self.entries.insert(oid.clone(), (value.clone(), timestamp));
+        Ok(())
+    }
+
+    /// Removes `oid` from the cache, appending a tombstone record so the
+    /// deletion survives a reopen until the next [`compact`](Self::compact).
+    pub fn delete(&mut self, oid: &Oid) -> Result<(), CacheError> {
+        self.append_record(oid, None, SystemTime::now())?;
+        self.entries.remove(oid);
+        Ok(())
+    }
+
+    fn append_record(
+        &mut self,
+        oid: &Oid,
+        value: Option<&Value>,
+        timestamp: SystemTime,
+    ) -> Result<(), CacheError> {
+        let plaintext = encode_record(oid, value, timestamp)?;
+        let framed = encode_frame(&self.key, self.next_nonce, &plaintext);
+        self.next_nonce += 1;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&(framed.len() as u32).to_be_bytes())?;
+        file.write_all(&framed)?;
+        Ok(())
+    }
+
+    /// Rewrites the cache file from the current in-memory entries,
+    /// dropping every superseded or deleted record the append log has
+    /// accumulated.
+    pub fn compact(&mut self) -> Result<(), CacheError> {
+        let tmp_path = self.path.with_extension("compact.tmp");
+        let mut tmp = File::create(&tmp_path)?;
+
+        for (oid, (value, timestamp)) in &self.entries {
+            let plaintext = encode_record(oid, Some(value), *timestamp)?;
+            let framed = encode_frame(&self.key, self.next_nonce, &plaintext);
+            self.next_nonce += 1;
+            tmp.write_all(&(framed.len() as u32).to_be_bytes())?;
+            tmp.write_all(&framed)?;
+        }
+        tmp.flush()?;
+        drop(tmp);
+
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+fn system_time_to_parts(t: SystemTime) -> (u64, u32) {
+    let since_epoch = t.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+    (since_epoch.as_secs(), since_epoch.subsec_nanos())
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Encodes one record as `oid_len || oid BER || present flag ||
+/// [value_len || value BER] || timestamp secs || timestamp nanos ||
+/// crc32(everything above)`.
+fn encode_record(
+    oid: &Oid,
+    value: Option<&Value>,
+    timestamp: SystemTime,
+) -> Result<Vec<u8>, CacheError> {
+    let oid_ber = oid.to_ber()?;
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&(oid_ber.len() as u32).to_be_bytes());
+    body.extend_from_slice(&oid_ber);
+
+    match value {
+        Some(value) => {
+            body.push(1);
+            let mut value_ber = Vec::new();
+            value.encode_ber(&mut value_ber);
+            body.extend_from_slice(&(value_ber.len() as u32).to_be_bytes());
+            body.extend_from_slice(&value_ber);
+        }
+        None => body.push(0),
+    }
+
+    let (secs, nanos) = system_time_to_parts(timestamp);
+    body.extend_from_slice(&secs.to_be_bytes());
+    body.extend_from_slice(&nanos.to_be_bytes());
+
+    let crc = crc32(&body);
+    body.extend_from_slice(&crc.to_be_bytes());
+    Ok(body)
+}
+
+/// The inverse of [`encode_record`], rejecting a record whose trailing
+/// CRC32 doesn't match its contents (a torn write or on-disk corruption)
+/// instead of returning bogus data.
+fn decode_record(body: &[u8]) -> Result<(Oid, Option<Value>, SystemTime), CacheError> {
+    if body.len() < 4 {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let (content, crc_bytes) = body.split_at(body.len() - 4);
+    let stored_crc = u32::from_be_bytes(crc_bytes.try_into().unwrap());
+    if crc32(content) != stored_crc {
+        return Err(io::Error::from(io::ErrorKind::InvalidData).into());
+    }
+
+    let mut cursor = 0usize;
+    let eof = || io::Error::from(io::ErrorKind::UnexpectedEof);
+
+    let oid_len = read_u32(content, &mut cursor).ok_or_else(eof)? as usize;
+    let oid_bytes = read_bytes(content, &mut cursor, oid_len).ok_or_else(eof)?;
+    let oid = Oid::from_ber(oid_bytes)?;
+
+    let present = *content.get(cursor).ok_or_else(eof)?;
+    cursor += 1;
+
+    let value = if present == 1 {
+        let value_len = read_u32(content, &mut cursor).ok_or_else(eof)? as usize;
+        let value_bytes = read_bytes(content, &mut cursor, value_len).ok_or_else(eof)?;
+        let mut value_cursor = Cursor::new(value_bytes);
+        Some(Value::decode_ber(&mut value_cursor)?)
+    } else {
+        None
+    };
+
+    let secs = read_u64(content, &mut cursor).ok_or_else(eof)?;
+    let nanos = read_u32(content, &mut cursor).ok_or_else(eof)?;
+    let timestamp = UNIX_EPOCH + Duration::new(secs, nanos);
+
+    Ok((oid, value, timestamp))
+}
+
+fn read_u32(bytes: &[u8], cursor: &mut usize) -> Option<u32> {
+    let slice = bytes.get(*cursor..*cursor + 4)?;
+    *cursor += 4;
+    Some(u32::from_be_bytes(slice.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &[u8], cursor: &mut usize) -> Option<u64> {
+    let slice = bytes.get(*cursor..*cursor + 8)?;
+    *cursor += 8;
+    Some(u64::from_be_bytes(slice.try_into().unwrap()))
+}
+
+fn read_bytes<'a>(bytes: &'a [u8], cursor: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let slice = bytes.get(*cursor..*cursor + len)?;
+    *cursor += len;
+    Some(slice)
+}
+
+#[cfg(feature = "cache-encryption")]
+fn encode_frame(key: &Option<[u8; 16]>, nonce: u64, plaintext: &[u8]) -> Vec<u8> {
+    let Some(key) = key else {
+        return plaintext.to_vec();
+    };
+
+    let mut iv = [0u8; 16];
+    iv[..8].copy_from_slice(&nonce.to_be_bytes());
+
+    let mut buf = plaintext.to_vec();
+    Aes128CfbEnc::new(key.into(), &iv.into()).encrypt(&mut buf);
+
+    let mut framed = Vec::with_capacity(iv.len() + buf.len());
+    framed.extend_from_slice(&iv);
+    framed.extend_from_slice(&buf);
+    framed
+}
+
+#[cfg(not(feature = "cache-encryption"))]
+fn encode_frame(_key: &Option<[u8; 16]>, _nonce: u64, plaintext: &[u8]) -> Vec<u8> {
+    plaintext.to_vec()
+}
+
+#[cfg(feature = "cache-encryption")]
+fn decode_frame(key: &Option<[u8; 16]>, framed: &[u8]) -> io::Result<Vec<u8>> {
+    let Some(key) = key else {
+        return Ok(framed.to_vec());
+    };
+    if framed.len() < 16 {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    let (iv, ciphertext) = framed.split_at(16);
+    let mut buf = ciphertext.to_vec();
+    Aes128CfbDec::new(key.into(), iv.into()).decrypt(&mut buf);
+    Ok(buf)
+}
+
+#[cfg(not(feature = "cache-encryption"))]
+fn decode_frame(_key: &Option<[u8; 16]>, framed: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(framed.to_vec())
+}
+
+/// Recovers the nonce an encrypted frame was written with, by reading it
+/// back out of the leading 8 bytes of the frame's IV (see [`encode_frame`]).
+/// Returns `None` for an unencrypted frame, since the nonce isn't stored
+/// anywhere in that case.
+#[cfg(feature = "cache-encryption")]
+fn extract_nonce(key: &Option<[u8; 16]>, framed: &[u8]) -> Option<u64> {
+    if key.is_none() || framed.len() < 8 {
+        return None;
+    }
+    Some(u64::from_be_bytes(framed[..8].try_into().unwrap()))
+}
+
+#[cfg(not(feature = "cache-encryption"))]
+fn extract_nonce(_key: &Option<[u8; 16]>, _framed: &[u8]) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "snmpkit-cache-test-{name}-{:?}",
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_crc32_known_vector() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_put_then_get() {
+        let path = temp_path("put-get");
+        let mut cache = OidCache::open(&path).unwrap();
+        let oid: Oid = "1.3.6.1.2.1.1.3.0".parse().unwrap();
+        cache.put(&oid, &Value::timeticks(42)).unwrap();
+
+        let (value, _) = cache.get(&oid).unwrap();
+        assert_eq!(value, Value::timeticks(42));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reopen_replays_existing_records() {
+        let path = temp_path("reopen");
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        {
+            let mut cache = OidCache::open(&path).unwrap();
+            cache.put(&oid, &Value::string("router1")).unwrap();
+        }
+
+        let reopened = OidCache::open(&path).unwrap();
+        let (value, _) = reopened.get(&oid).unwrap();
+        assert_eq!(value, Value::string("router1"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_put_overwrites_previous_value() {
+        let path = temp_path("overwrite");
+        let mut cache = OidCache::open(&path).unwrap();
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        cache.put(&oid, &Value::string("old")).unwrap();
+        cache.put(&oid, &Value::string("new")).unwrap();
+
+        let (value, _) = cache.get(&oid).unwrap();
+        assert_eq!(value, Value::string("new"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_delete_removes_entry_and_survives_reopen() {
+        let path = temp_path("delete");
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        {
+            let mut cache = OidCache::open(&path).unwrap();
+            cache.put(&oid, &Value::string("gone-soon")).unwrap();
+            cache.delete(&oid).unwrap();
+            assert_eq!(cache.get(&oid), None);
+        }
+
+        let reopened = OidCache::open(&path).unwrap();
+        assert_eq!(reopened.get(&oid), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_trailing_record_is_skipped_not_fatal() {
+        let path = temp_path("torn");
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        {
+            let mut cache = OidCache::open(&path).unwrap();
+            cache.put(&oid, &Value::string("safe")).unwrap();
+        }
+        // Simulate a crash mid-append: a length prefix with no matching body.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&100u32.to_be_bytes()).unwrap();
+        file.write_all(b"short").unwrap();
+        drop(file);
+
+        let reopened = OidCache::open(&path).unwrap();
+        let (value, _) = reopened.get(&oid).unwrap();
+        assert_eq!(value, Value::string("safe"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_record_is_skipped_not_fatal() {
+        let path = temp_path("corrupt");
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        {
+            let mut cache = OidCache::open(&path).unwrap();
+            cache.put(&oid, &Value::string("safe")).unwrap();
+        }
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff; // flip a bit in the trailing CRC32
+        fs::write(&path, &bytes).unwrap();
+
+        let reopened = OidCache::open(&path).unwrap();
+        assert_eq!(reopened.get(&oid), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compact_drops_superseded_and_deleted_keys() {
+        let path = temp_path("compact");
+        let kept: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        let deleted: Oid = "1.3.6.1.2.1.1.6.0".parse().unwrap();
+
+        let mut cache = OidCache::open(&path).unwrap();
+        cache.put(&kept, &Value::string("v1")).unwrap();
+        cache.put(&kept, &Value::string("v2")).unwrap();
+        cache.put(&deleted, &Value::string("temporary")).unwrap();
+        cache.delete(&deleted).unwrap();
+
+        let size_before_compaction = fs::metadata(&path).unwrap().len();
+        cache.compact().unwrap();
+        let size_after_compaction = fs::metadata(&path).unwrap().len();
+        assert!(size_after_compaction < size_before_compaction);
+
+        let reopened = OidCache::open(&path).unwrap();
+        assert_eq!(reopened.get(&kept).unwrap().0, Value::string("v2"));
+        assert_eq!(reopened.get(&deleted), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "cache-encryption")]
+    #[test]
+    fn test_encrypted_cache_roundtrips() {
+        let path = temp_path("encrypted");
+        let key = [7u8; 16];
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        {
+            let mut cache = OidCache::open_encrypted(&path, key).unwrap();
+            cache.put(&oid, &Value::string("secret-ish")).unwrap();
+        }
+
+        let reopened = OidCache::open_encrypted(&path, key).unwrap();
+        let (value, _) = reopened.get(&oid).unwrap();
+        assert_eq!(value, Value::string("secret-ish"));
+
+        // The plaintext value shouldn't appear verbatim on disk.
+        let bytes = fs::read(&path).unwrap();
+        assert!(!bytes
+            .windows(b"secret-ish".len())
+            .any(|w| w == b"secret-ish"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "cache-encryption")]
+    #[test]
+    fn test_reopen_resumes_nonce_sequence_instead_of_reusing_ivs() {
+        let path = temp_path("nonce-resume");
+        let key = [7u8; 16];
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+
+        let mut cache = OidCache::open_encrypted(&path, key).unwrap();
+        cache.put(&oid, &Value::string("one")).unwrap();
+        cache.put(&oid, &Value::string("two")).unwrap();
+        drop(cache);
+
+        // Simulates a process restart: a freshly opened cache must not
+        // restart its nonce from 0, or the next record would reuse an IV
+        // already used with this key.
+        let mut reopened = OidCache::open_encrypted(&path, key).unwrap();
+        assert_eq!(reopened.next_nonce, 2);
+        reopened.put(&oid, &Value::string("three")).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let mut ivs = Vec::new();
+        let mut cursor = 0usize;
+        while cursor + 4 <= bytes.len() {
+            let frame_len =
+                u32::from_be_bytes(bytes[cursor..cursor + 4].try_into().unwrap()) as usize;
+            cursor += 4;
+            ivs.push(bytes[cursor..cursor + 16].to_vec());
+            cursor += frame_len;
+        }
+        let unique_ivs: std::collections::HashSet<_> = ivs.iter().collect();
+        assert_eq!(unique_ivs.len(), ivs.len(), "IV reused across records");
+        fs::remove_file(&path).unwrap();
+    }
+}
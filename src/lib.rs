@@ -1,8 +1,14 @@
 use pyo3::prelude::*;
 
 pub mod agentx;
+mod ber;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod mib;
 pub mod oid;
+pub mod trap;
 pub mod types;
+pub mod usm;
 
 #[pymodule(name = "core")]
 fn snmpkit_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -0,0 +1,405 @@
+//! MIB symbol resolution: mapping numeric [`Oid`]s to the symbolic names
+//! object definitions give them, and back.
+//!
+//! A [`MibRegistry`] holds object definitions (name, base OID, declared SMI
+//! syntax, units, and enum labels); [`MibRegistry::with_builtins`] preloads
+//! the common RFC1213/SNMPv2-MIB `system` and `ifTable` objects. Most
+//! callers don't need their own registry: the free functions in this module
+//! ([`register`], [`to_symbolic`], [`parse_symbolic`], [`render_value`])
+//! operate on a process-wide default registry seeded with those same
+//! built-ins, so a tool can register its vendor MIB's objects once at
+//! startup and then print every walked OID symbolically.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+use crate::oid::{Oid, OidError};
+use crate::types::Value;
+
+/// Errors resolving a symbolic name to an [`Oid`] or registering one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MibError {
+    /// No registered object has this name.
+    UnknownName(String),
+    /// The dotted suffix after the object name (e.g. the `.0` in
+    /// `sysDescr.0`) is not a valid sequence of sub-identifiers.
+    InvalidInstanceSuffix(String),
+    /// The resulting OID (object OID plus instance suffix) is invalid.
+    InvalidOid(OidError),
+}
+
+impl fmt::Display for MibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MibError::UnknownName(s) => write!(f, "unknown MIB object name: {s}"),
+            MibError::InvalidInstanceSuffix(s) => {
+                write!(f, "invalid instance suffix: {s}")
+            }
+            MibError::InvalidOid(e) => write!(f, "invalid OID: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MibError {}
+
+/// A MIB object's declared definition: its symbolic name, base OID, SMI
+/// syntax, optional units clause, and (for enumerated INTEGER syntaxes)
+/// the mapping from each integer value to its label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectDef {
+    pub name: String,
+    pub oid: Oid,
+    pub syntax: String,
+    pub units: Option<String>,
+    pub enum_labels: Vec<(i32, String)>,
+}
+
+impl ObjectDef {
+    /// A bare name/OID pair with no declared syntax, as registered by
+    /// [`register`] for callers that only care about name resolution.
+    fn bare(name: impl Into<String>, oid: Oid) -> Self {
+        Self {
+            name: name.into(),
+            oid,
+            syntax: String::new(),
+            units: None,
+            enum_labels: Vec::new(),
+        }
+    }
+}
+
+/// A set of MIB object definitions, resolvable by name or by OID.
+///
+/// Resolution against an instance OID (e.g. `sysDescr.0`, a scalar's `.0`
+/// instance, or an `ifDescr.3` table row) finds the registered object whose
+/// OID is the longest matching prefix, then reports the remaining arcs as
+/// the instance suffix.
+#[derive(Debug, Clone, Default)]
+pub struct MibRegistry {
+    by_oid: BTreeMap<Oid, ObjectDef>,
+    by_name: HashMap<String, Oid>,
+}
+
+impl MibRegistry {
+    /// An empty registry with no objects defined.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry preloaded with the common RFC1213/SNMPv2-MIB `system`
+    /// and `ifTable` objects.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for def in builtin_objects() {
+            registry.register_object(def);
+        }
+        registry
+    }
+
+    /// Registers a bare name → OID mapping, with no declared syntax or
+    /// enum labels.
+    pub fn register(&mut self, name: impl Into<String>, oid: Oid) {
+        self.register_object(ObjectDef::bare(name, oid));
+    }
+
+    /// Registers a full object definition, including its SMI syntax, units,
+    /// and (if applicable) enum labels.
+    pub fn register_object(&mut self, def: ObjectDef) {
+        self.by_name.insert(def.name.clone(), def.oid.clone());
+        self.by_oid.insert(def.oid.clone(), def);
+    }
+
+    /// Finds the registered object whose OID is the longest prefix of
+    /// `oid`, if any.
+    fn resolve_prefix(&self, oid: &Oid) -> Option<&ObjectDef> {
+        self.by_oid
+            .range(..=oid.clone())
+            .rev()
+            .find(|(base, _)| oid.starts_with(base))
+            .map(|(_, def)| def)
+    }
+
+    /// Looks up the full definition of a registered object by OID, without
+    /// the instance-suffix handling [`to_symbolic`](Self::to_symbolic)
+    /// does.
+    pub fn lookup(&self, oid: &Oid) -> Option<&ObjectDef> {
+        self.by_oid.get(oid)
+    }
+
+    /// Renders `oid` as `name` or `name.instance...`, or `None` if no
+    /// registered object's OID is a prefix of it.
+    pub fn to_symbolic(&self, oid: &Oid) -> Option<String> {
+        let def = self.resolve_prefix(oid)?;
+        let suffix = &oid.parts()[def.oid.parts().len()..];
+        if suffix.is_empty() {
+            return Some(def.name.clone());
+        }
+        let suffix: Vec<String> = suffix.iter().map(u32::to_string).collect();
+        Some(format!("{}.{}", def.name, suffix.join(".")))
+    }
+
+    /// Parses `name` or `name.instance...` (e.g. `"sysDescr.0"`) into an
+    /// [`Oid`], resolving `name` against this registry.
+    pub fn parse_symbolic(&self, s: &str) -> Result<Oid, MibError> {
+        let s = s.trim();
+        let (name, suffix) = s.split_once('.').map_or((s, None), |(n, r)| (n, Some(r)));
+
+        let base = self
+            .by_name
+            .get(name)
+            .ok_or_else(|| MibError::UnknownName(name.to_string()))?;
+
+        let Some(suffix) = suffix else {
+            return Ok(base.clone());
+        };
+
+        let mut parts = base.parts().to_vec();
+        for part in suffix.split('.') {
+            let arc: u32 = part
+                .parse()
+                .map_err(|_| MibError::InvalidInstanceSuffix(suffix.to_string()))?;
+            parts.push(arc);
+        }
+        Oid::new(parts).map_err(MibError::InvalidOid)
+    }
+
+    /// Renders `value` as text, substituting the object's declared enum
+    /// label (e.g. `"up(1)"`) for an `Integer` value when `oid` resolves to
+    /// an object with enum labels, falling back to `value`'s own `Display`
+    /// otherwise.
+    pub fn render_value(&self, oid: &Oid, value: &Value) -> String {
+        if let (Some(def), Some(n)) = (self.resolve_prefix(oid), value.as_integer()) {
+            if let Some((_, label)) = def.enum_labels.iter().find(|(v, _)| *v == n) {
+                return format!("{label}({n})");
+            }
+        }
+        value.to_string()
+    }
+}
+
+/// RFC1213/SNMPv2-MIB `system` group objects (`1.3.6.1.2.1.1`) and the
+/// `ifTable` columns most tools need (`1.3.6.1.2.1.2.2.1`).
+fn builtin_objects() -> Vec<ObjectDef> {
+    fn oid(parts: &[u32]) -> Oid {
+        Oid::from_slice(parts).expect("built-in MIB OIDs are never empty")
+    }
+
+    vec![
+        ObjectDef::bare("sysDescr", oid(&[1, 3, 6, 1, 2, 1, 1, 1])),
+        ObjectDef::bare("sysObjectID", oid(&[1, 3, 6, 1, 2, 1, 1, 2])),
+        ObjectDef::bare("sysUpTime", oid(&[1, 3, 6, 1, 2, 1, 1, 3])),
+        ObjectDef::bare("sysContact", oid(&[1, 3, 6, 1, 2, 1, 1, 4])),
+        ObjectDef::bare("sysName", oid(&[1, 3, 6, 1, 2, 1, 1, 5])),
+        ObjectDef::bare("sysLocation", oid(&[1, 3, 6, 1, 2, 1, 1, 6])),
+        ObjectDef::bare("sysServices", oid(&[1, 3, 6, 1, 2, 1, 1, 7])),
+        ObjectDef::bare("ifNumber", oid(&[1, 3, 6, 1, 2, 1, 2, 1])),
+        ObjectDef::bare("ifIndex", oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1])),
+        ObjectDef::bare("ifDescr", oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2])),
+        ObjectDef {
+            name: "ifType".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 3]),
+            syntax: "INTEGER".to_string(),
+            units: None,
+            enum_labels: vec![
+                (1, "other".to_string()),
+                (6, "ethernetCsmacd".to_string()),
+                (23, "ppp".to_string()),
+                (24, "softwareLoopback".to_string()),
+            ],
+        },
+        ObjectDef::bare("ifMtu", oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 4])),
+        ObjectDef {
+            name: "ifSpeed".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 5]),
+            syntax: "Gauge32".to_string(),
+            units: Some("bits per second".to_string()),
+            enum_labels: Vec::new(),
+        },
+        ObjectDef::bare("ifPhysAddress", oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 6])),
+        ObjectDef {
+            name: "ifAdminStatus".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 7]),
+            syntax: "INTEGER".to_string(),
+            units: None,
+            enum_labels: vec![
+                (1, "up".to_string()),
+                (2, "down".to_string()),
+                (3, "testing".to_string()),
+            ],
+        },
+        ObjectDef {
+            name: "ifOperStatus".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 8]),
+            syntax: "INTEGER".to_string(),
+            units: None,
+            enum_labels: vec![
+                (1, "up".to_string()),
+                (2, "down".to_string()),
+                (3, "testing".to_string()),
+                (4, "unknown".to_string()),
+                (5, "dormant".to_string()),
+                (6, "notPresent".to_string()),
+                (7, "lowerLayerDown".to_string()),
+            ],
+        },
+        ObjectDef {
+            name: "ifInOctets".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 10]),
+            syntax: "Counter32".to_string(),
+            units: Some("octets".to_string()),
+            enum_labels: Vec::new(),
+        },
+        ObjectDef {
+            name: "ifOutOctets".to_string(),
+            oid: oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 16]),
+            syntax: "Counter32".to_string(),
+            units: Some("octets".to_string()),
+            enum_labels: Vec::new(),
+        },
+    ]
+}
+
+static DEFAULT_REGISTRY: OnceLock<RwLock<MibRegistry>> = OnceLock::new();
+
+fn default_registry() -> &'static RwLock<MibRegistry> {
+    DEFAULT_REGISTRY.get_or_init(|| RwLock::new(MibRegistry::with_builtins()))
+}
+
+/// Registers `name` as an alias for `oid` in the process-wide default
+/// registry, so later calls to [`to_symbolic`]/[`parse_symbolic`] resolve
+/// it.
+pub fn register(name: impl Into<String>, oid: Oid) {
+    default_registry()
+        .write()
+        .expect("MIB registry lock poisoned")
+        .register(name, oid);
+}
+
+/// Resolves `oid` against the process-wide default registry. See
+/// [`MibRegistry::to_symbolic`].
+pub fn to_symbolic(oid: &Oid) -> Option<String> {
+    default_registry()
+        .read()
+        .expect("MIB registry lock poisoned")
+        .to_symbolic(oid)
+}
+
+/// Parses a symbolic name against the process-wide default registry. See
+/// [`MibRegistry::parse_symbolic`].
+pub fn parse_symbolic(s: &str) -> Result<Oid, MibError> {
+    default_registry()
+        .read()
+        .expect("MIB registry lock poisoned")
+        .parse_symbolic(s)
+}
+
+/// Renders `value` against the process-wide default registry. See
+/// [`MibRegistry::render_value`].
+pub fn render_value(oid: &Oid, value: &Value) -> String {
+    default_registry()
+        .read()
+        .expect("MIB registry lock poisoned")
+        .render_value(oid, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_symbolic_scalar_instance() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.2.1.1.1.0".parse().unwrap();
+        assert_eq!(registry.to_symbolic(&oid).unwrap(), "sysDescr.0");
+    }
+
+    #[test]
+    fn test_to_symbolic_object_itself() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.2.1.1.1".parse().unwrap();
+        assert_eq!(registry.to_symbolic(&oid).unwrap(), "sysDescr");
+    }
+
+    #[test]
+    fn test_to_symbolic_unregistered_returns_none() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.4.1.99999.1".parse().unwrap();
+        assert_eq!(registry.to_symbolic(&oid), None);
+    }
+
+    #[test]
+    fn test_to_symbolic_table_column_instance() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.2.1.2.2.1.2.3".parse().unwrap();
+        assert_eq!(registry.to_symbolic(&oid).unwrap(), "ifDescr.3");
+    }
+
+    #[test]
+    fn test_parse_symbolic_with_instance() {
+        let registry = MibRegistry::with_builtins();
+        let oid = registry.parse_symbolic("sysDescr.0").unwrap();
+        assert_eq!(oid.to_string(), "1.3.6.1.2.1.1.1.0");
+    }
+
+    #[test]
+    fn test_parse_symbolic_object_name_only() {
+        let registry = MibRegistry::with_builtins();
+        let oid = registry.parse_symbolic("sysName").unwrap();
+        assert_eq!(oid.to_string(), "1.3.6.1.2.1.1.5");
+    }
+
+    #[test]
+    fn test_parse_symbolic_unknown_name() {
+        let registry = MibRegistry::with_builtins();
+        assert!(matches!(
+            registry.parse_symbolic("notARealObject.0"),
+            Err(MibError::UnknownName(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_symbolic_invalid_suffix() {
+        let registry = MibRegistry::with_builtins();
+        assert!(matches!(
+            registry.parse_symbolic("sysDescr.abc"),
+            Err(MibError::InvalidInstanceSuffix(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_runtime_mapping() {
+        let mut registry = MibRegistry::new();
+        let oid: Oid = "1.3.6.1.4.1.12345.1".parse().unwrap();
+        registry.register("myVendorObject", oid.clone());
+        assert_eq!(registry.to_symbolic(&oid).unwrap(), "myVendorObject");
+        assert_eq!(registry.parse_symbolic("myVendorObject").unwrap(), oid);
+    }
+
+    #[test]
+    fn test_render_value_with_enum_label() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.2.1.2.2.1.8.1".parse().unwrap();
+        let value = Value::integer(1);
+        assert_eq!(registry.render_value(&oid, &value), "up(1)");
+    }
+
+    #[test]
+    fn test_render_value_without_enum_label_falls_back_to_display() {
+        let registry = MibRegistry::with_builtins();
+        let oid: Oid = "1.3.6.1.2.1.1.5.0".parse().unwrap();
+        let value = Value::string("router1");
+        assert_eq!(registry.render_value(&oid, &value), "router1");
+    }
+
+    #[test]
+    fn test_default_registry_free_functions() {
+        let oid: Oid = "1.3.6.1.2.1.1.1.0".parse().unwrap();
+        assert_eq!(to_symbolic(&oid).unwrap(), "sysDescr.0");
+        assert_eq!(parse_symbolic("sysDescr.0").unwrap(), oid);
+
+        let vendor_oid: Oid = "1.3.6.1.4.1.55555.7".parse().unwrap();
+        register("testVendorObject", vendor_oid.clone());
+        assert_eq!(to_symbolic(&vendor_oid).unwrap(), "testVendorObject");
+    }
+}
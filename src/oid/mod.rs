@@ -79,6 +79,23 @@ impl Oid {
         parts.push(sub_id);
         Oid { parts }
     }
+
+    #[pyo3(name = "to_ber")]
+    fn py_to_ber(&self) -> PyResult<Vec<u8>> {
+        self.to_ber()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_ber")]
+    fn py_from_ber(bytes: Vec<u8>) -> PyResult<Self> {
+        Self::from_ber(&bytes).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "to_symbolic")]
+    fn py_to_symbolic(&self) -> String {
+        self.to_symbolic()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -86,6 +103,7 @@ pub enum OidError {
     Empty,
     InvalidFormat(String),
     InvalidPart(String),
+    InvalidBer(String),
 }
 
 impl fmt::Display for OidError {
@@ -94,6 +112,7 @@ impl fmt::Display for OidError {
             OidError::Empty => write!(f, "OID cannot be empty"),
             OidError::InvalidFormat(s) => write!(f, "invalid OID format: {s}"),
             OidError::InvalidPart(s) => write!(f, "invalid OID part: {s}"),
+            OidError::InvalidBer(s) => write!(f, "invalid BER-encoded OID: {s}"),
         }
     }
 }
@@ -154,6 +173,127 @@ impl Oid {
             .take_while(|(a, b)| a == b)
             .count()
     }
+
+    /// Encodes this OID's arcs as BER/ASN.1 `OBJECT IDENTIFIER` content
+    /// bytes (no tag or length octets), using the standard `40*x + y`
+    /// combined first arc plus base-128 continuation encoding for the
+    /// rest. This is the wire form AgentX PDUs (e.g. a GetBulk search
+    /// range's subtree bound) carry OIDs in, so a decoded PDU can feed a
+    /// [`crate::oid::OidTrie`] directly without a string round-trip, or an
+    /// OID can be read straight out of a captured packet via
+    /// [`from_ber`](Self::from_ber) without going through the string form.
+    ///
+    /// The combined first arc requires at least two sub-identifiers, so
+    /// an OID with fewer than two arcs is rejected.
+    pub fn to_ber(&self) -> Result<Vec<u8>, OidError> {
+        if self.parts.len() < 2 {
+            return Err(OidError::InvalidBer(
+                "at least two arcs are required to encode the combined first arc".to_string(),
+            ));
+        }
+
+        let first = self.parts[0]
+            .checked_mul(40)
+            .and_then(|v| v.checked_add(self.parts[1]))
+            .ok_or_else(|| {
+                OidError::InvalidBer(format!(
+                    "first two arcs {}.{} overflow when packed",
+                    self.parts[0], self.parts[1]
+                ))
+            })?;
+
+        let mut buf = Vec::new();
+        encode_ber_subidentifier(first, &mut buf);
+        for &part in &self.parts[2..] {
+            encode_ber_subidentifier(part, &mut buf);
+        }
+        Ok(buf)
+    }
+
+    /// Decodes BER/ASN.1 `OBJECT IDENTIFIER` content bytes (no tag or
+    /// length octets) produced by [`to_ber`](Self::to_ber).
+    ///
+    /// Rejects empty content, a truncated trailing sub-identifier (a
+    /// continuation bit left set at the end of `bytes`), an overlong
+    /// sub-identifier (a leading `0x80` byte, which is a non-minimal and
+    /// therefore reserved encoding), and a sub-identifier whose value
+    /// overflows `u32`, surfacing each as [`OidError::InvalidBer`]
+    /// instead of panicking.
+    pub fn from_ber(bytes: &[u8]) -> Result<Oid, OidError> {
+        if bytes.is_empty() {
+            return Err(OidError::InvalidBer("empty BER content".to_string()));
+        }
+
+        let subids = decode_ber_subidentifiers(bytes)?;
+        let first = subids[0];
+        // The root arc 2 (joint-iso-itu-t) has no 40-way limit on its child
+        // arc, so `first` can run past what `x*40 + y` could otherwise
+        // represent unambiguously for x in {0, 1}; the X.690 convention
+        // resolves this by reserving first >= 80 entirely for root arc 2.
+        let (x, y) = if first < 80 {
+            (first / 40, first % 40)
+        } else {
+            (2, first - 80)
+        };
+        let mut parts = vec![x, y];
+        parts.extend_from_slice(&subids[1..]);
+        Oid::new(parts)
+    }
+
+    /// Resolves this OID against the process-wide default MIB registry
+    /// (built-ins plus anything registered at runtime, see [`crate::mib`]),
+    /// falling back to the plain dotted-decimal form when no object
+    /// definition matches.
+    pub fn to_symbolic(&self) -> String {
+        crate::mib::to_symbolic(self).unwrap_or_else(|| self.to_string())
+    }
+}
+
+fn encode_ber_subidentifier(mut value: u32, buf: &mut Vec<u8>) {
+    let mut chunks = [0u8; 5];
+    let mut n = 0;
+    loop {
+        chunks[n] = (value & 0x7f) as u8;
+        value >>= 7;
+        n += 1;
+        if value == 0 {
+            break;
+        }
+    }
+    for (i, &chunk) in chunks[..n].iter().rev().enumerate() {
+        let continuation = if i == n - 1 { 0x00 } else { 0x80 };
+        buf.push(chunk | continuation);
+    }
+}
+
+fn decode_ber_subidentifiers(bytes: &[u8]) -> Result<Vec<u32>, OidError> {
+    let mut out = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == 0x80 {
+            return Err(OidError::InvalidBer(
+                "overlong sub-identifier (leading 0x80 byte)".to_string(),
+            ));
+        }
+
+        let mut value: u64 = 0;
+        loop {
+            if idx >= bytes.len() {
+                return Err(OidError::InvalidBer("truncated sub-identifier".to_string()));
+            }
+            let b = bytes[idx];
+            value = (value << 7) | (b & 0x7f) as u64;
+            idx += 1;
+            if value > u32::MAX as u64 {
+                return Err(OidError::InvalidBer("sub-identifier overflow".to_string()));
+            }
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        out.push(value as u32);
+    }
+    Ok(out)
 }
 
 impl FromStr for Oid {
@@ -198,6 +338,39 @@ impl Ord for Oid {
     }
 }
 
+/// Serializes as the dotted-string form for human-readable formats (JSON,
+/// YAML, ...) and as the raw `Vec<u32>` otherwise, so a MIB snapshot shipped
+/// over a binary IPC format doesn't pay the string round-trip cost.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Oid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_string())
+        } else {
+            self.parts.serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Oid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            let parts = Vec::<u32>::deserialize(deserializer)?;
+            Oid::new(parts).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,4 +446,95 @@ mod tests {
         assert!(!parent.is_parent_of(&sibling));
         assert!(!parent.is_parent_of(&parent));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_roundtrip_is_dotted_string() {
+        let oid: Oid = "1.3.6.1.4.1.12345".parse().unwrap();
+        let json = serde_json::to_string(&oid).unwrap();
+        assert_eq!(json, "\"1.3.6.1.4.1.12345\"");
+        assert_eq!(serde_json::from_str::<Oid>(&json).unwrap(), oid);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_bincode_roundtrip_is_parts_vec() {
+        let oid: Oid = "1.3.6.1.4.1".parse().unwrap();
+        let bytes = bincode::serialize(&oid).unwrap();
+        assert_eq!(bincode::deserialize::<Oid>(&bytes).unwrap(), oid);
+    }
+
+    #[test]
+    fn test_ber_roundtrip() {
+        let oid: Oid = "1.3.6.1.4.1.12345.1".parse().unwrap();
+        let ber = oid.to_ber().unwrap();
+        assert_eq!(Oid::from_ber(&ber).unwrap(), oid);
+    }
+
+    #[test]
+    fn test_ber_roundtrip_two_arcs() {
+        let oid: Oid = "2.999".parse().unwrap();
+        let ber = oid.to_ber().unwrap();
+        assert_eq!(ber, vec![0x88, 0x37]);
+        assert_eq!(Oid::from_ber(&ber).unwrap(), oid);
+    }
+
+    #[test]
+    fn test_ber_encode_rejects_fewer_than_two_arcs() {
+        let oid: Oid = "1".parse().unwrap();
+        assert!(matches!(oid.to_ber(), Err(OidError::InvalidBer(_))));
+    }
+
+    #[test]
+    fn test_ber_decode_rejects_empty_content() {
+        assert!(matches!(Oid::from_ber(&[]), Err(OidError::InvalidBer(_))));
+    }
+
+    #[test]
+    fn test_ber_decode_rejects_truncated_subidentifier() {
+        // 0x87 has its continuation bit set but there is no following byte.
+        assert!(matches!(
+            Oid::from_ber(&[0x2b, 0x87]),
+            Err(OidError::InvalidBer(_))
+        ));
+    }
+
+    #[test]
+    fn test_ber_decode_rejects_overlong_subidentifier() {
+        // A sub-identifier may not start with a 0x80 continuation byte.
+        assert!(matches!(
+            Oid::from_ber(&[0x2b, 0x80, 0x01]),
+            Err(OidError::InvalidBer(_))
+        ));
+    }
+
+    #[test]
+    fn test_ber_decode_rejects_high_bit_set_on_final_byte() {
+        // The last byte of a sub-identifier must have its continuation bit
+        // clear; 0x81 here leaves it set with nothing following.
+        assert!(matches!(
+            Oid::from_ber(&[0x2b, 0x81]),
+            Err(OidError::InvalidBer(_))
+        ));
+    }
+
+    #[test]
+    fn test_ber_decode_rejects_subidentifier_overflow() {
+        assert!(matches!(
+            Oid::from_ber(&[0x2b, 0x8f, 0xff, 0xff, 0xff, 0xff, 0x7f]),
+            Err(OidError::InvalidBer(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_symbolic_resolves_builtin_object() {
+        let oid: Oid = "1.3.6.1.2.1.1.1.0".parse().unwrap();
+        assert_eq!(oid.to_symbolic(), "sysDescr.0");
+    }
+
+    #[test]
+    fn test_to_symbolic_falls_back_to_dotted_decimal() {
+        let oid: Oid = "1.3.6.1.4.1.99999.1".parse().unwrap();
+        assert_eq!(oid.to_symbolic(), "1.3.6.1.4.1.99999.1");
+    }
 }
@@ -1,14 +1,31 @@
-//! OID Trie - A radix trie optimized for SNMP OID lookups.
+//! OID Trie - A path-compressed (Patricia) radix trie optimized for SNMP
+//! OID lookups.
 //!
 //! Provides O(k) lookup where k is the OID depth, with efficient
-//! `get_next` for SNMP GETNEXT operations.
+//! `get_next` for SNMP GETNEXT operations. Chains of single-child nodes
+//! are collapsed into one node carrying a multi-arc edge label, so a deep
+//! enterprise OID doesn't allocate one `BTreeMap`-bearing node per
+//! sub-identifier.
 
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use super::Oid;
+use crate::types::Value;
+
+fn common_prefix_len(a: &[u32], b: &[u32]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
 
 #[derive(Debug, Clone)]
 struct TrieNode<V> {
+    // The run of sub-identifiers shared by every OID in this node's
+    // subtree since the last branch point (or since the root, for a
+    // direct child of it). Always non-empty except for the root node.
+    label: Vec<u32>,
     value: Option<V>,
     children: BTreeMap<u32, TrieNode<V>>,
 }
@@ -16,6 +33,7 @@ struct TrieNode<V> {
 impl<V> Default for TrieNode<V> {
     fn default() -> Self {
         Self {
+            label: Vec::new(),
             value: None,
             children: BTreeMap::new(),
         }
@@ -29,8 +47,14 @@ impl<V> Default for TrieNode<V> {
 /// - O(k) longest prefix matching for registration lookups
 /// - O(k + m) get_next for GETNEXT where m is nodes traversed
 ///
-/// Uses `BTreeMap` for children to maintain lexicographic ordering,
-/// which is essential for correct SNMP GETNEXT behavior.
+/// Internally, chains of single-child nodes are collapsed into one node
+/// with a multi-arc edge label (à la a Patricia trie), so a sparse tree
+/// of deep enterprise OIDs doesn't pay for a `BTreeMap` at every
+/// sub-identifier. `insert` splits an edge when a new key diverges
+/// mid-label; `remove` merges a node back into its single remaining
+/// child when pruning leaves it without a value. Children are still
+/// keyed in a `BTreeMap` (by the first arc of each edge label) to
+/// maintain the lexicographic ordering GETNEXT depends on.
 #[derive(Debug, Clone)]
 pub struct OidTrie<V> {
     root: TrieNode<V>,
@@ -72,39 +96,106 @@ impl<V> OidTrie<V> {
     ///
     /// Returns the previous value if the OID was already present.
     pub fn insert(&mut self, oid: &Oid, value: V) -> Option<V> {
-        let mut node = &mut self.root;
-
-        for &part in oid.parts() {
-            node = node.children.entry(part).or_default();
-        }
-
-        let old = node.value.replace(value);
-        if old.is_none() {
+        let (old, grew) = Self::insert_rec(&mut self.root.children, oid.parts(), value);
+        if grew {
             self.len += 1;
         }
         old
     }
 
-    /// Returns a reference to the value at the given OID.
-    pub fn get(&self, oid: &Oid) -> Option<&V> {
-        let mut node = &self.root;
-
-        for &part in oid.parts() {
-            node = node.children.get(&part)?;
+    /// Inserts `value` at `parts` somewhere within `children`, splitting an
+    /// existing edge if `parts` diverges mid-label. Returns the replaced
+    /// value (if any) and whether the trie gained a new entry.
+    fn insert_rec(
+        children: &mut BTreeMap<u32, TrieNode<V>>,
+        parts: &[u32],
+        value: V,
+    ) -> (Option<V>, bool) {
+        let key = parts[0];
+
+        match children.entry(key) {
+            Entry::Vacant(slot) => {
+                slot.insert(TrieNode {
+                    label: parts.to_vec(),
+                    value: Some(value),
+                    children: BTreeMap::new(),
+                });
+                (None, true)
+            }
+            Entry::Occupied(mut slot) => {
+                let child = slot.get_mut();
+                let cpl = common_prefix_len(&child.label, parts);
+
+                if cpl == child.label.len() && cpl == parts.len() {
+                    let old = child.value.replace(value);
+                    let grew = old.is_none();
+                    (old, grew)
+                } else if cpl == child.label.len() {
+                    Self::insert_rec(&mut child.children, &parts[cpl..], value)
+                } else {
+                    // The new key diverges from this edge partway through
+                    // its label - split it at the common prefix, pushing
+                    // the old suffix down into a new intermediate node.
+                    let rest_label = child.label[cpl..].to_vec();
+                    let rest_key = rest_label[0];
+                    let rest_node = TrieNode {
+                        label: rest_label,
+                        value: child.value.take(),
+                        children: std::mem::take(&mut child.children),
+                    };
+                    child.label.truncate(cpl);
+                    child.children.insert(rest_key, rest_node);
+
+                    if cpl == parts.len() {
+                        // The new OID ends exactly at the split point.
+                        child.value = Some(value);
+                    } else {
+                        let new_key = parts[cpl];
+                        child.children.insert(
+                            new_key,
+                            TrieNode {
+                                label: parts[cpl..].to_vec(),
+                                value: Some(value),
+                                children: BTreeMap::new(),
+                            },
+                        );
+                    }
+                    (None, true)
+                }
+            }
         }
+    }
 
-        node.value.as_ref()
+    /// Returns a reference to the value at the given OID.
+    pub fn get(&self, oid: &Oid) -> Option<&V> {
+        Self::find_node(&self.root, oid.parts()).and_then(|node| node.value.as_ref())
     }
 
     /// Returns a mutable reference to the value at the given OID.
     pub fn get_mut(&mut self, oid: &Oid) -> Option<&mut V> {
-        let mut node = &mut self.root;
+        Self::find_node_mut(&mut self.root, oid.parts()).and_then(|node| node.value.as_mut())
+    }
 
-        for &part in oid.parts() {
-            node = node.children.get_mut(&part)?;
+    fn find_node<'a>(node: &'a TrieNode<V>, parts: &[u32]) -> Option<&'a TrieNode<V>> {
+        if parts.is_empty() {
+            return Some(node);
+        }
+        let child = node.children.get(&parts[0])?;
+        if !parts.starts_with(&child.label) {
+            return None;
         }
+        Self::find_node(child, &parts[child.label.len()..])
+    }
 
-        node.value.as_mut()
+    fn find_node_mut<'a>(node: &'a mut TrieNode<V>, parts: &[u32]) -> Option<&'a mut TrieNode<V>> {
+        if parts.is_empty() {
+            return Some(node);
+        }
+        let child = node.children.get_mut(&parts[0])?;
+        if !parts.starts_with(&child.label) {
+            return None;
+        }
+        Self::find_node_mut(child, &parts[child.label.len()..])
     }
 
     /// Returns `true` if the trie contains the given OID.
@@ -114,34 +205,53 @@ impl<V> OidTrie<V> {
 
     /// Removes and returns the value at the given OID.
     ///
-    /// Also prunes empty ancestor nodes to prevent memory leaks.
+    /// Also prunes empty nodes and merges a node left with a single
+    /// child and no value back into that child, to keep the trie's edge
+    /// compression canonical.
     pub fn remove(&mut self, oid: &Oid) -> Option<V> {
-        let parts = oid.parts();
-        let removed = Self::remove_recursive(&mut self.root, parts, 0);
+        let removed = Self::remove_rec(&mut self.root.children, oid.parts());
         if removed.is_some() {
             self.len -= 1;
         }
         removed
     }
 
-    fn remove_recursive(node: &mut TrieNode<V>, parts: &[u32], depth: usize) -> Option<V> {
-        if depth == parts.len() {
-            return node.value.take();
-        }
+    fn remove_rec(children: &mut BTreeMap<u32, TrieNode<V>>, parts: &[u32]) -> Option<V> {
+        let key = parts[0];
+        let removed;
+        let prune;
 
-        let part = parts[depth];
+        {
+            let child = children.get_mut(&key)?;
+            if !parts.starts_with(&child.label) {
+                return None;
+            }
+            let rest = &parts[child.label.len()..];
 
-        if let Some(child) = node.children.get_mut(&part) {
-            let value = Self::remove_recursive(child, parts, depth + 1);
+            removed = if rest.is_empty() {
+                child.value.take()
+            } else {
+                Self::remove_rec(&mut child.children, rest)
+            };
 
-            if child.value.is_none() && child.children.is_empty() {
-                node.children.remove(&part);
-            }
+            prune = child.value.is_none() && child.children.is_empty();
+        }
 
-            value
+        if prune {
+            children.remove(&key);
         } else {
-            None
+            let child = children.get_mut(&key).unwrap();
+            if child.value.is_none() && child.children.len() == 1 {
+                let only_key = *child.children.keys().next().unwrap();
+                let mut only = child.children.remove(&only_key).unwrap();
+                let mut merged_label = std::mem::take(&mut child.label);
+                merged_label.extend_from_slice(&only.label);
+                only.label = merged_label;
+                *child = only;
+            }
         }
+
+        removed
     }
 
     /// Finds the longest OID prefix that has a value.
@@ -149,151 +259,270 @@ impl<V> OidTrie<V> {
     /// Used for finding which registration handles a given OID.
     /// Returns the matching OID and its value.
     pub fn longest_prefix(&self, oid: &Oid) -> Option<(Oid, &V)> {
-        let mut node = &self.root;
-        let mut last_match: Option<(usize, &V)> = None;
         let parts = oid.parts();
-        let mut matched_depth = 0;
+        let mut node = &self.root;
+        let mut remaining = parts;
+        let mut consumed = 0usize;
+        let mut last_match: Option<(usize, &V)> = node.value.as_ref().map(|v| (0, v));
 
-        for &part in parts {
-            if let Some(ref v) = node.value {
-                last_match = Some((matched_depth, v));
+        loop {
+            if remaining.is_empty() {
+                break;
             }
-
-            match node.children.get(&part) {
-                Some(child) => {
-                    node = child;
-                    matched_depth += 1;
-                }
-                None => break,
+            let Some(child) = node.children.get(&remaining[0]) else {
+                break;
+            };
+            let cpl = common_prefix_len(&child.label, remaining);
+            if cpl < child.label.len() {
+                break;
             }
-        }
 
-        // Check if final node has a value
-        if let Some(ref v) = node.value {
-            last_match = Some((matched_depth, v));
+            consumed += cpl;
+            remaining = &remaining[cpl..];
+            node = child;
+            if let Some(ref v) = node.value {
+                last_match = Some((consumed, v));
+            }
         }
 
-        last_match.map(|(depth, v)| {
-            let matched_parts = &parts[..depth];
-            (Oid::new(matched_parts.to_vec()).unwrap(), v)
-        })
+        last_match.map(|(depth, v)| (Oid::new(parts[..depth].to_vec()).unwrap(), v))
     }
 
     /// Finds the next OID after the given one in lexicographic order.
     ///
-    /// This is the core operation for SNMP GETNEXT. The algorithm:
-    /// 1. Navigate to the target OID's position in the trie
-    /// 2. If we're at the exact OID, look for children or siblings
-    /// 3. If we're past the target, find the first value in current subtree
-    /// 4. Use BTreeMap's ordering to find the next sibling when needed
+    /// This is the core operation for SNMP GETNEXT.
     pub fn get_next(&self, oid: &Oid) -> Option<(Oid, &V)> {
-        let mut path = Vec::with_capacity(oid.parts().len() + 4);
-        let result = Self::find_next(&self.root, &mut path, oid.parts(), 0);
-        result.map(|(parts, v)| (Oid::new(parts).unwrap(), v))
+        self.range_iter(oid, false).next()
+    }
+
+    /// Returns an iterator over all (OID, value) pairs in lexicographic order.
+    pub fn iter(&self) -> TrieIter<'_, V> {
+        TrieIter::new(&self.root)
     }
 
-    /// Recursive helper for get_next.
+    /// Returns an iterator over all OIDs in lexicographic order.
+    pub fn keys(&self) -> impl Iterator<Item = Oid> + '_ {
+        self.iter().map(|(oid, _)| oid)
+    }
+
+    /// Returns an iterator over all values in lexicographic OID order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Returns an iterator over every entry in the subtree rooted at
+    /// `prefix`, including `prefix` itself if it has a value, navigating to
+    /// the prefix node in O(k) before walking its descendants.
     ///
-    /// Returns the path to the next value and a reference to it.
-    fn find_next<'a>(
-        node: &'a TrieNode<V>,
-        path: &mut Vec<u32>,
-        target: &[u32],
-        depth: usize,
-    ) -> Option<(Vec<u32>, &'a V)> {
-        if depth < target.len() {
-            // Still navigating toward target - need to go deeper or find sibling
-            let target_part = target[depth];
-
-            // Look at children >= target_part
-            for (&part, child) in node.children.range(target_part..) {
-                path.push(part);
-
-                let result = if part == target_part {
-                    // Exact match - continue deeper
-                    Self::find_next(child, path, target, depth + 1)
-                } else {
-                    // Found a sibling > target - return first value in its subtree
-                    Self::first_in_subtree(child, path)
-                };
+    /// Unlike [`range_iter`](Self::range_iter), iteration never escapes the
+    /// subtree: it stops once every descendant of `prefix` is exhausted.
+    pub fn iter_subtree(&self, prefix: &Oid) -> TrieIter<'_, V> {
+        let mut node = &self.root;
+        let mut consumed: Vec<u32> = Vec::new();
+        let mut remaining = prefix.parts();
 
-                if result.is_some() {
-                    return result;
-                }
-                path.pop();
-            }
-            None
-        } else if depth == target.len() {
-            // At exact target depth - look for children (deeper OIDs)
-            for (&part, child) in &node.children {
-                path.push(part);
-                if let Some(result) = Self::first_in_subtree(child, path) {
-                    return Some(result);
-                }
-                path.pop();
+        loop {
+            if remaining.is_empty() {
+                return TrieIter::rooted(node, consumed);
             }
-            None
-        } else {
-            // Past target (target is prefix of current path) - return this node if it has value
-            if let Some(ref v) = node.value {
-                return Some((path.clone(), v));
-            }
-
-            // Otherwise find first value in any child
-            for (&part, child) in &node.children {
-                path.push(part);
-                if let Some(result) = Self::first_in_subtree(child, path) {
-                    return Some(result);
-                }
-                path.pop();
+            let Some(child) = node.children.get(&remaining[0]) else {
+                return TrieIter::empty();
+            };
+            let cpl = common_prefix_len(&child.label, remaining);
+
+            if cpl == remaining.len() {
+                // `prefix` ends at or before the end of this edge - every
+                // OID reachable from `child` extends `prefix`.
+                consumed.extend_from_slice(&child.label);
+                return TrieIter::rooted(child, consumed);
+            } else if cpl == child.label.len() {
+                consumed.extend_from_slice(&child.label);
+                remaining = &remaining[cpl..];
+                node = child;
+            } else {
+                return TrieIter::empty();
             }
-            None
         }
     }
 
-    /// Finds the first value in a subtree (depth-first, lexicographic order).
-    fn first_in_subtree<'a>(
-        node: &'a TrieNode<V>,
-        path: &mut Vec<u32>,
-    ) -> Option<(Vec<u32>, &'a V)> {
-        if let Some(ref v) = node.value {
-            return Some((path.clone(), v));
+    /// Collects every `(Oid, &V)` pair under `prefix` into a `Vec`.
+    pub fn entries_under(&self, prefix: &Oid) -> Vec<(Oid, &V)> {
+        self.iter_subtree(prefix).collect()
+    }
+
+    /// Returns an iterator over entries at or after `start` in lexicographic
+    /// OID order, without restarting the descent from the root between
+    /// calls to `next()`.
+    ///
+    /// When `inclusive` is `true`, an exact match at `start` is yielded
+    /// first; otherwise iteration begins strictly after `start`, mirroring
+    /// `get_next`.
+    pub fn range_iter(&self, start: &Oid, inclusive: bool) -> TrieIter<'_, V> {
+        TrieIter::seeked(&self.root, start.parts(), inclusive)
+    }
+
+    /// Returns an iterator over entries at or after `start`, positioning
+    /// the cursor directly at the right spot instead of repeatedly calling
+    /// `get_next` from the root for each step of a bulk walk.
+    ///
+    /// Equivalent to `range_iter(start, true)`.
+    pub fn iter_from(&self, start: &Oid) -> TrieIter<'_, V> {
+        self.range_iter(start, true)
+    }
+
+    /// Returns up to `n` successors of `oid` in a single depth-first pass,
+    /// for servicing one GETBULK search range without re-descending from
+    /// the root for every repetition. Like `get_next`, the first returned
+    /// element is strictly greater than `oid`; returns fewer than `n`
+    /// entries once the subtree is exhausted.
+    pub fn get_bulk(&self, oid: &Oid, n: usize) -> Vec<(Oid, &V)> {
+        self.range_iter(oid, false).take(n).collect()
+    }
+
+    /// Services a whole GETBULK request (possibly several search ranges)
+    /// against this trie in a single pass per range.
+    ///
+    /// `ranges` is a list of `(start, inclusive)` search ranges, where
+    /// `inclusive` only affects the first (non-repeater) successor — once a
+    /// range starts repeating it always advances with `get_next` semantics,
+    /// matching the protocol. The first `non_repeaters` ranges each
+    /// contribute exactly one successor; the remaining ranges each
+    /// contribute up to `max_repetitions` successors, assembled column-major
+    /// the way GETBULK responses are ordered. A range that runs out of
+    /// entries yields `None` for its remaining slots (the caller maps this
+    /// to `endOfMibView`) rather than restarting.
+    pub fn get_bulk_ranges(
+        &self,
+        ranges: &[(Oid, bool)],
+        non_repeaters: usize,
+        max_repetitions: usize,
+    ) -> Vec<Option<(Oid, &V)>> {
+        let split = non_repeaters.min(ranges.len());
+        let mut out = Vec::with_capacity(split + (ranges.len() - split) * max_repetitions);
+
+        for (start, inclusive) in &ranges[..split] {
+            out.push(self.range_iter(start, *inclusive).next());
         }
 
-        for (&part, child) in &node.children {
-            path.push(part);
-            if let Some(result) = Self::first_in_subtree(child, path) {
-                return Some(result);
+        let columns: Vec<Vec<(Oid, &V)>> = ranges[split..]
+            .iter()
+            .map(|(start, _)| self.get_bulk(start, max_repetitions))
+            .collect();
+
+        for rep in 0..max_repetitions {
+            for col in &columns {
+                out.push(col.get(rep).cloned());
             }
-            path.pop();
         }
-        None
+
+        out
     }
+}
 
-    /// Returns an iterator over all (OID, value) pairs in lexicographic order.
-    pub fn iter(&self) -> TrieIter<'_, V> {
-        TrieIter::new(&self.root)
+impl OidTrie<Value> {
+    /// Walks the subtree rooted at `base` in lexicographic order, the way a
+    /// GETNEXT-driven (or GETBULK-driven) SNMP walk advances one entry at a
+    /// time, stopping cleanly instead of running past the subtree or
+    /// spinning forever on malformed data.
+    ///
+    /// The walk ends, without yielding a final entry, as soon as: the next
+    /// entry's OID is no longer within `base`'s subtree (checked
+    /// component-wise via [`Oid::starts_with`], not by string prefix, so
+    /// `1.3.6.1.2.1.1` can't match `1.3.6.1.2.1.10`); the next entry's value
+    /// is [`Value::EndOfMibView`] or [`Value::NoSuchObject`]; or the next
+    /// entry's OID is not strictly greater than the previous one, which
+    /// would otherwise loop forever.
+    pub fn walk(&self, base: &Oid) -> WalkIter<'_> {
+        WalkIter {
+            trie: self,
+            base: base.clone(),
+            last: None,
+            done: false,
+        }
     }
+}
 
-    /// Returns an iterator over all OIDs in lexicographic order.
-    pub fn keys(&self) -> impl Iterator<Item = Oid> + '_ {
-        self.iter().map(|(oid, _)| oid)
+/// Iterator returned by [`OidTrie::walk`].
+pub struct WalkIter<'a> {
+    trie: &'a OidTrie<Value>,
+    base: Oid,
+    last: Option<Oid>,
+    done: bool,
+}
+
+impl<'a> Iterator for WalkIter<'a> {
+    type Item = (Oid, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let from = self.last.as_ref().unwrap_or(&self.base);
+        let Some((oid, value)) = self.trie.get_next(from) else {
+            self.done = true;
+            return None;
+        };
+
+        let in_subtree = oid.starts_with(&self.base);
+        let advanced = self.last.as_ref().is_none_or(|last| oid > *last);
+        let is_terminal = matches!(value, Value::EndOfMibView | Value::NoSuchObject);
+
+        if !in_subtree || !advanced || is_terminal {
+            self.done = true;
+            return None;
+        }
+
+        self.last = Some(oid.clone());
+        Some((oid, value))
     }
+}
 
-    /// Returns an iterator over all values in lexicographic OID order.
-    pub fn values(&self) -> impl Iterator<Item = &V> {
-        self.iter().map(|(_, v)| v)
+/// Serializes as an ordered sequence of `(Oid, V)` pairs (lexicographic OID
+/// order), so a MIB snapshot round-trips through both JSON (for inspection)
+/// and a binary format (for compact on-disk caching) without exposing the
+/// internal node layout.
+#[cfg(feature = "serde")]
+impl<V: Serialize> Serialize for OidTrie<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for (oid, value) in self.iter() {
+            seq.serialize_element(&(oid, value))?;
+        }
+        seq.end()
     }
 }
 
-/// Iterator over trie entries in lexicographic OID order.
+#[cfg(feature = "serde")]
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for OidTrie<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let entries = Vec::<(Oid, V)>::deserialize(deserializer)?;
+        let mut trie = OidTrie::new();
+        for (oid, value) in entries {
+            trie.insert(&oid, value);
+        }
+        Ok(trie)
+    }
+}
+
+type ChildRange<'a, V> = std::collections::btree_map::Range<'a, u32, TrieNode<V>>;
+
+/// Iterator over trie entries in lexicographic OID order, optionally seeked
+/// to start at or after an arbitrary OID.
 pub struct TrieIter<'a, V> {
-    // Stack of (node, children iterator)
-    #[allow(clippy::type_complexity)]
-    stack: Vec<(
-        &'a TrieNode<V>,
-        std::collections::btree_map::Iter<'a, u32, TrieNode<V>>,
-    )>,
+    // Stack of (node, remaining-children iterator, node's own edge-label
+    // length), innermost node last. The label length lets `next()` rewind
+    // `path` by the right amount when a node's children are exhausted,
+    // since a single node may now represent several sub-identifiers.
+    stack: Vec<(&'a TrieNode<V>, ChildRange<'a, V>, usize)>,
     path: Vec<u32>,
     // Pending value from current node (before descending into children)
     pending: Option<&'a V>,
@@ -301,13 +530,150 @@ pub struct TrieIter<'a, V> {
 
 impl<'a, V> TrieIter<'a, V> {
     fn new(root: &'a TrieNode<V>) -> Self {
-        let mut iter = Self {
+        Self::rooted(root, Vec::new())
+    }
+
+    /// Builds an iterator over `node` and its descendants, treating `path`
+    /// as the absolute OID prefix of `node` so emitted OIDs are absolute.
+    fn rooted(node: &'a TrieNode<V>, path: Vec<u32>) -> Self {
+        Self {
+            stack: vec![(node, node.children.range(..), 0)],
+            pending: node.value.as_ref(),
+            path,
+        }
+    }
+
+    /// An iterator that yields nothing, used when a requested prefix isn't
+    /// present in the trie at all.
+    fn empty() -> Self {
+        Self {
             stack: Vec::new(),
             path: Vec::new(),
-            pending: root.value.as_ref(),
-        };
-        iter.stack.push((root, root.children.iter()));
-        iter
+            pending: None,
+        }
+    }
+
+    /// Builds an iterator positioned at or after `target`, seeking directly
+    /// to the right spot in O(depth) instead of skipping past earlier
+    /// entries.
+    fn seeked(root: &'a TrieNode<V>, target: &[u32], inclusive: bool) -> Self {
+        let mut path = Vec::with_capacity(target.len());
+        let mut stack = Vec::new();
+        let pending = Self::seek(root, target, 0, inclusive, &mut path, &mut stack);
+        Self {
+            stack,
+            path,
+            pending,
+        }
+    }
+
+    /// Descends from `node` toward `target[depth..]`, pushing a resumable
+    /// frame for every node passed through so that once this call returns,
+    /// plain forward iteration (`next()`) continues exactly where it left
+    /// off - at the next sibling subtree, or back up to the parent's.
+    fn seek(
+        node: &'a TrieNode<V>,
+        target: &[u32],
+        depth: usize,
+        inclusive: bool,
+        path: &mut Vec<u32>,
+        stack: &mut Vec<(&'a TrieNode<V>, ChildRange<'a, V>, usize)>,
+    ) -> Option<&'a V> {
+        if depth == target.len() {
+            stack.push((node, node.children.range(..), node.label.len()));
+            return if inclusive { node.value.as_ref() } else { None };
+        }
+
+        let want = target[depth];
+
+        if let Some(child) = node.children.get(&want) {
+            let target_rest = &target[depth..];
+            let cpl = common_prefix_len(&child.label, target_rest);
+
+            if cpl == child.label.len() {
+                // The whole edge matches; keep descending toward target.
+                let siblings = node
+                    .children
+                    .range((std::ops::Bound::Excluded(want), std::ops::Bound::Unbounded));
+                stack.push((node, siblings, node.label.len()));
+                path.extend_from_slice(&child.label);
+                return Self::seek(child, target, depth + cpl, inclusive, path, stack);
+            }
+
+            if cpl == target_rest.len() || child.label[cpl] > target_rest[cpl] {
+                // target sits at or before this edge's divergence point:
+                // everything in child's subtree is >= target.
+                let siblings = node
+                    .children
+                    .range((std::ops::Bound::Excluded(want), std::ops::Bound::Unbounded));
+                stack.push((node, siblings, node.label.len()));
+                path.extend_from_slice(&child.label);
+                return Self::descend_leftmost(child, path, stack);
+            }
+
+            // target diverges to something greater than child's whole
+            // subtree - skip it and try the next sibling.
+            let mut siblings = node
+                .children
+                .range((std::ops::Bound::Excluded(want), std::ops::Bound::Unbounded));
+            return match siblings.next() {
+                Some((_, sibling)) => {
+                    stack.push((node, siblings, node.label.len()));
+                    path.extend_from_slice(&sibling.label);
+                    Self::descend_leftmost(sibling, path, stack)
+                }
+                None => {
+                    stack.push((
+                        node,
+                        node.children
+                            .range((std::ops::Bound::Excluded(want), std::ops::Bound::Unbounded)),
+                        node.label.len(),
+                    ));
+                    None
+                }
+            };
+        }
+
+        // No child keyed exactly at `want`; the smallest key greater than
+        // it (if any) is the next candidate subtree.
+        let mut range = node.children.range(want..);
+        match range.next() {
+            Some((_, child)) => {
+                stack.push((node, range, node.label.len()));
+                path.extend_from_slice(&child.label);
+                Self::descend_leftmost(child, path, stack)
+            }
+            None => {
+                stack.push((node, node.children.range(want..), node.label.len()));
+                None
+            }
+        }
+    }
+
+    /// After diverging onto a strictly-greater sibling, descends to the
+    /// first value in its subtree while leaving the path resumable.
+    fn descend_leftmost(
+        node: &'a TrieNode<V>,
+        path: &mut Vec<u32>,
+        stack: &mut Vec<(&'a TrieNode<V>, ChildRange<'a, V>, usize)>,
+    ) -> Option<&'a V> {
+        if node.value.is_some() {
+            stack.push((node, node.children.range(..), node.label.len()));
+            return node.value.as_ref();
+        }
+
+        let mut range = node.children.range(..);
+        match range.next() {
+            Some((_, child)) => {
+                stack.push((node, range, node.label.len()));
+                path.extend_from_slice(&child.label);
+                Self::descend_leftmost(child, path, stack)
+            }
+            None => {
+                stack.push((node, node.children.range(..), node.label.len()));
+                None
+            }
+        }
     }
 }
 
@@ -321,18 +687,20 @@ impl<'a, V> Iterator for TrieIter<'a, V> {
         }
 
         // Depth-first traversal
-        while let Some((_, children_iter)) = self.stack.last_mut() {
-            if let Some((&part, child)) = children_iter.next() {
-                self.path.push(part);
-                self.stack.push((child, child.children.iter()));
+        while let Some((_, children_iter, _)) = self.stack.last_mut() {
+            if let Some((_, child)) = children_iter.next() {
+                self.path.extend_from_slice(&child.label);
+                self.stack
+                    .push((child, child.children.range(..), child.label.len()));
 
                 if let Some(ref v) = child.value {
                     return Some((Oid::new(self.path.clone()).unwrap(), v));
                 }
             } else {
                 // No more children at this level, go back up
-                self.stack.pop();
-                self.path.pop();
+                let (_, _, label_len) = self.stack.pop().unwrap();
+                let new_len = self.path.len() - label_len;
+                self.path.truncate(new_len);
             }
         }
 
@@ -521,4 +889,389 @@ mod tests {
         let trie: OidTrie<&str> = OidTrie::new();
         assert_eq!(trie.iter().count(), 0);
     }
+
+    #[test]
+    fn test_range_iter_inclusive_exclusive() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.1.2".parse().unwrap(), "b");
+        trie.insert(&"1.3.6.1.3".parse().unwrap(), "c");
+
+        let start: Oid = "1.3.6.1.2".parse().unwrap();
+        let items: Vec<_> = trie.range_iter(&start, true).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0.to_string(), "1.3.6.1.2");
+        assert_eq!(items[1].0.to_string(), "1.3.6.1.3");
+
+        let items: Vec<_> = trie.range_iter(&start, false).collect();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0.to_string(), "1.3.6.1.3");
+    }
+
+    #[test]
+    fn test_range_iter_nonexistent_start() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.1.5".parse().unwrap(), "b");
+
+        let query: Oid = "1.3.6.1.3".parse().unwrap();
+        let items: Vec<_> = trie.range_iter(&query, true).collect();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0.to_string(), "1.3.6.1.5");
+    }
+
+    #[test]
+    fn test_range_iter_matches_full_iter_order() {
+        let mut trie = OidTrie::new();
+        for s in ["1.3.6.1.1", "1.3.6.1.2", "1.3.6.2", "1.3.6.2.1"] {
+            trie.insert(&s.parse().unwrap(), s);
+        }
+        let all: Vec<_> = trie.iter().collect();
+        let start: Oid = "1.3.6.1.1".parse().unwrap();
+        let from_start: Vec<_> = trie.range_iter(&start, true).collect();
+        assert_eq!(all.len(), from_start.len());
+        for (a, b) in all.iter().zip(from_start.iter()) {
+            assert_eq!(a.0, b.0);
+        }
+    }
+
+    #[test]
+    fn test_range_iter_empty_trie() {
+        let trie: OidTrie<&str> = OidTrie::new();
+        let query: Oid = "1.3.6.1".parse().unwrap();
+        assert_eq!(trie.range_iter(&query, true).count(), 0);
+    }
+
+    #[test]
+    fn test_get_bulk_single_range() {
+        let mut trie = OidTrie::new();
+        for (i, s) in ["1.3.6.1.1", "1.3.6.1.2", "1.3.6.1.3", "1.3.6.1.4"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(&s.parse().unwrap(), i);
+        }
+
+        let start: Oid = "1.3.6.1.1".parse().unwrap();
+        let results = trie.get_bulk(&start, 2);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.to_string(), "1.3.6.1.2");
+        assert_eq!(results[1].0.to_string(), "1.3.6.1.3");
+    }
+
+    #[test]
+    fn test_get_bulk_single_range_stops_early_when_exhausted() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "only");
+
+        let start: Oid = "1.3.6.1".parse().unwrap();
+        let results = trie.get_bulk(&start, 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.to_string(), "1.3.6.1.1");
+    }
+
+    #[test]
+    fn test_get_bulk_ranges_non_repeaters_and_repeaters() {
+        let mut trie = OidTrie::new();
+        for (i, s) in ["1.3.6.1.1", "1.3.6.1.2", "1.3.6.1.3", "1.3.6.2.1"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(&s.parse().unwrap(), i);
+        }
+
+        let ranges = vec![
+            ("1.3.6.1".parse().unwrap(), false),
+            ("1.3.6.1.1".parse().unwrap(), false),
+        ];
+        let result = trie.get_bulk_ranges(&ranges, 1, 2);
+
+        assert_eq!(result[0].as_ref().unwrap().0.to_string(), "1.3.6.1.1");
+        assert_eq!(result[1].as_ref().unwrap().0.to_string(), "1.3.6.1.2");
+        assert_eq!(result[2].as_ref().unwrap().0.to_string(), "1.3.6.1.3");
+    }
+
+    #[test]
+    fn test_get_bulk_ranges_exhausted_range_yields_none() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "only");
+
+        let ranges = vec![("1.3.6.1.1".parse().unwrap(), false)];
+        let result = trie.get_bulk_ranges(&ranges, 0, 3);
+        assert_eq!(result, vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_iter_subtree_includes_prefix_value() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1".parse().unwrap(), "parent");
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "child1");
+        trie.insert(&"1.3.6.1.2".parse().unwrap(), "child2");
+        trie.insert(&"1.3.6.2".parse().unwrap(), "unrelated");
+
+        let prefix: Oid = "1.3.6.1".parse().unwrap();
+        let entries: Vec<_> = trie.iter_subtree(&prefix).collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].0.to_string(), "1.3.6.1");
+        assert_eq!(entries[1].0.to_string(), "1.3.6.1.1");
+        assert_eq!(entries[2].0.to_string(), "1.3.6.1.2");
+    }
+
+    #[test]
+    fn test_iter_subtree_stops_at_boundary() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.2".parse().unwrap(), "b");
+
+        let prefix: Oid = "1.3.6.1".parse().unwrap();
+        let entries: Vec<_> = trie.iter_subtree(&prefix).collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0.to_string(), "1.3.6.1.1");
+    }
+
+    #[test]
+    fn test_iter_subtree_missing_prefix() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1".parse().unwrap(), "a");
+
+        let prefix: Oid = "1.3.6.9".parse().unwrap();
+        assert_eq!(trie.iter_subtree(&prefix).count(), 0);
+    }
+
+    #[test]
+    fn test_iter_subtree_prefix_ends_mid_compressed_edge() {
+        // With path compression, 1.3.6.1.4.1.12345 collapses onto one
+        // edge when it's the only entry below 1.3.6.1 - make sure a
+        // prefix landing partway through that edge still finds it.
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.4.1.12345".parse().unwrap(), "enterprise");
+
+        let prefix: Oid = "1.3.6.1.4".parse().unwrap();
+        let entries: Vec<_> = trie.iter_subtree(&prefix).collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0.to_string(), "1.3.6.1.4.1.12345");
+    }
+
+    #[test]
+    fn test_iter_from_positions_at_or_after_start() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.1.2".parse().unwrap(), "b");
+        trie.insert(&"1.3.6.2".parse().unwrap(), "c");
+
+        let start: Oid = "1.3.6.1.2".parse().unwrap();
+        let items: Vec<_> = trie.iter_from(&start).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0.to_string(), "1.3.6.1.2");
+        assert_eq!(items[1].0.to_string(), "1.3.6.2");
+    }
+
+    #[test]
+    fn test_iter_from_walks_a_whole_bulk_request_in_one_pass() {
+        let mut trie = OidTrie::new();
+        for i in 1..=5u32 {
+            trie.insert(&Oid::new(vec![1, 3, 6, i]).unwrap(), i);
+        }
+
+        let start: Oid = Oid::new(vec![1, 3, 6, 1]).unwrap();
+        let walked: Vec<_> = trie.iter_from(&start).map(|(_, v)| *v).collect();
+        assert_eq!(walked, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_entries_under() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.1.2".parse().unwrap(), "b");
+
+        let prefix: Oid = "1.3.6.1".parse().unwrap();
+        let entries = trie.entries_under(&prefix);
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_splits_on_divergent_insert() {
+        // A single deep entry collapses onto one compressed edge; a
+        // sibling that diverges partway through it must split the edge
+        // into a branch node without disturbing the original value.
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.4.1.12345".parse().unwrap(), "enterprise");
+        trie.insert(&"1.3.6.1.4.1.99999".parse().unwrap(), "other-enterprise");
+        trie.insert(&"1.3.6.1.2.1".parse().unwrap(), "mib-2");
+
+        assert_eq!(trie.len(), 3);
+        assert_eq!(
+            trie.get(&"1.3.6.1.4.1.12345".parse().unwrap()),
+            Some(&"enterprise")
+        );
+        assert_eq!(
+            trie.get(&"1.3.6.1.4.1.99999".parse().unwrap()),
+            Some(&"other-enterprise")
+        );
+        assert_eq!(trie.get(&"1.3.6.1.2.1".parse().unwrap()), Some(&"mib-2"));
+
+        let items: Vec<_> = trie.iter().map(|(oid, _)| oid.to_string()).collect();
+        assert_eq!(
+            items,
+            vec!["1.3.6.1.2.1", "1.3.6.1.4.1.12345", "1.3.6.1.4.1.99999"]
+        );
+    }
+
+    #[test]
+    fn test_edge_splits_when_new_key_is_a_prefix_of_existing_label() {
+        // Inserting a shorter OID that is itself a prefix of an already
+        // compressed edge must split that edge and attach the value to
+        // the new branch node, not create a duplicate entry.
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.4.1.12345".parse().unwrap(), "deep");
+        trie.insert(&"1.3.6.1.4".parse().unwrap(), "branch");
+
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get(&"1.3.6.1.4".parse().unwrap()), Some(&"branch"));
+        assert_eq!(
+            trie.get(&"1.3.6.1.4.1.12345".parse().unwrap()),
+            Some(&"deep")
+        );
+        assert_eq!(
+            trie.longest_prefix(&"1.3.6.1.4.9".parse().unwrap())
+                .map(|(oid, v)| (oid.to_string(), *v)),
+            Some(("1.3.6.1.4".to_string(), "branch"))
+        );
+    }
+
+    #[test]
+    fn test_remove_merges_branch_with_its_only_remaining_child() {
+        // After removing one of two entries sharing a compressed prefix,
+        // the branch node left with a single child and no value of its
+        // own must merge back into that child.
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.4.1.12345".parse().unwrap(), "a");
+        trie.insert(&"1.3.6.1.4.1.99999".parse().unwrap(), "b");
+
+        assert_eq!(
+            trie.remove(&"1.3.6.1.4.1.99999".parse().unwrap()),
+            Some("b")
+        );
+        assert_eq!(trie.len(), 1);
+        assert_eq!(trie.get(&"1.3.6.1.4.1.12345".parse().unwrap()), Some(&"a"));
+
+        // The merge must not disturb ordinary lookups or GETNEXT walks.
+        assert_eq!(
+            trie.get_next(&"1.3.6.1".parse().unwrap())
+                .map(|(oid, v)| (oid.to_string(), *v)),
+            Some(("1.3.6.1.4.1.12345".to_string(), "a"))
+        );
+    }
+
+    #[test]
+    fn test_remove_prunes_leaf_without_merging_when_parent_has_value() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1".parse().unwrap(), "parent");
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), "child");
+
+        assert_eq!(trie.remove(&"1.3.6.1.1".parse().unwrap()), Some("child"));
+        assert_eq!(trie.len(), 1);
+        assert_eq!(trie.get(&"1.3.6.1".parse().unwrap()), Some(&"parent"));
+        assert!(trie.get_next(&"1.3.6.1".parse().unwrap()).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_roundtrip() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1".parse().unwrap(), 1u32);
+        trie.insert(&"1.3.6.2".parse().unwrap(), 2u32);
+        trie.insert(&"1.3.6.1.1".parse().unwrap(), 3u32);
+
+        let json = serde_json::to_string(&trie).unwrap();
+        let restored: OidTrie<u32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.len(), trie.len());
+        let original: Vec<_> = trie.iter().map(|(oid, v)| (oid, *v)).collect();
+        let roundtripped: Vec<_> = restored.iter().map(|(oid, v)| (oid, *v)).collect();
+        assert_eq!(original, roundtripped);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_bincode_roundtrip() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.4.1".parse().unwrap(), "a".to_string());
+        trie.insert(&"1.3.6.1.4.2".parse().unwrap(), "b".to_string());
+
+        let bytes = bincode::serialize(&trie).unwrap();
+        let restored: OidTrie<String> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(
+            restored.get(&"1.3.6.1.4.1".parse().unwrap()),
+            Some(&"a".to_string())
+        );
+        assert_eq!(
+            restored.get(&"1.3.6.1.4.2".parse().unwrap()),
+            Some(&"b".to_string())
+        );
+        assert_eq!(restored.len(), 2);
+    }
+
+    #[test]
+    fn test_walk_yields_subtree_in_order_and_stops() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.2.1.1.1".parse().unwrap(), Value::integer(1));
+        trie.insert(&"1.3.6.1.2.1.1.2".parse().unwrap(), Value::integer(2));
+        trie.insert(&"1.3.6.1.2.1.2.1".parse().unwrap(), Value::integer(3));
+
+        let base: Oid = "1.3.6.1.2.1.1".parse().unwrap();
+        let walked: Vec<Oid> = trie.walk(&base).map(|(oid, _)| oid).collect();
+
+        assert_eq!(
+            walked,
+            vec![
+                "1.3.6.1.2.1.1.1".parse::<Oid>().unwrap(),
+                "1.3.6.1.2.1.1.2".parse::<Oid>().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_does_not_confuse_numeric_prefix_siblings() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.2.1.1".parse().unwrap(), Value::integer(1));
+        trie.insert(&"1.3.6.1.2.1.10".parse().unwrap(), Value::integer(2));
+
+        let base: Oid = "1.3.6.1.2.1.1".parse().unwrap();
+        let walked: Vec<Oid> = trie.walk(&base).map(|(oid, _)| oid).collect();
+
+        // `1.3.6.1.2.1.10` must not be mistaken for a descendant of
+        // `1.3.6.1.2.1.1` just because "10" starts with "1".
+        assert!(walked.is_empty());
+    }
+
+    #[test]
+    fn test_walk_stops_on_end_of_mib_view() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.2.1.1.1".parse().unwrap(), Value::integer(1));
+        trie.insert(&"1.3.6.1.2.1.1.2".parse().unwrap(), Value::EndOfMibView);
+        trie.insert(&"1.3.6.1.2.1.1.3".parse().unwrap(), Value::integer(3));
+
+        let base: Oid = "1.3.6.1.2.1.1".parse().unwrap();
+        let walked: Vec<Oid> = trie.walk(&base).map(|(oid, _)| oid).collect();
+
+        assert_eq!(walked, vec!["1.3.6.1.2.1.1.1".parse::<Oid>().unwrap()]);
+    }
+
+    #[test]
+    fn test_walk_stops_on_no_such_object() {
+        let mut trie = OidTrie::new();
+        trie.insert(&"1.3.6.1.2.1.1.1".parse().unwrap(), Value::NoSuchObject);
+
+        let base: Oid = "1.3.6.1.2.1.1".parse().unwrap();
+        assert_eq!(trie.walk(&base).next(), None);
+    }
+
+    #[test]
+    fn test_walk_empty_subtree() {
+        let trie: OidTrie<Value> = OidTrie::new();
+        let base: Oid = "1.3.6.1".parse().unwrap();
+        assert_eq!(trie.walk(&base).count(), 0);
+    }
 }
@@ -0,0 +1,515 @@
+//! SNMP trap/inform receiver: binds a UDP socket and turns incoming
+//! SNMPv1 Trap-PDUs and SNMPv2c SNMPv2-Trap-PDUs/InformRequest-PDUs into a
+//! structured [`TrapEvent`], acknowledging each InformRequest with a
+//! Response-PDU as RFC 3416 requires.
+//!
+//! [`TrapListener`] is a blocking [`Iterator`] of `Result<TrapEvent,
+//! TrapError>`, so a monitoring tool can just `for event in listener { ... }`.
+
+use std::fmt;
+use std::io::{self, Cursor};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::oid::Oid;
+use crate::types::{BerError, Value};
+
+const TAG_SEQUENCE: u8 = 0x30;
+
+const PDU_GET_RESPONSE: u8 = 0xa2;
+const PDU_TRAP_V1: u8 = 0xa4;
+const PDU_INFORM_REQUEST: u8 = 0xa6;
+const PDU_TRAP_V2: u8 = 0xa7;
+
+/// Standard v1 generic-trap OIDs (RFC 3584 section 3.2), the well-known
+/// `snmpTraps` subtree `1.3.6.1.6.3.1.1.5`.
+const GENERIC_TRAP_OIDS: [&[u32]; 6] = [
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 1], // coldStart
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 2], // warmStart
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 3], // linkDown
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 4], // linkUp
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 5], // authenticationFailure
+    &[1, 3, 6, 1, 6, 3, 1, 1, 5, 6], // egpNeighborLoss
+];
+
+/// A parsed trap or inform notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrapEvent {
+    /// The notification's identity: `snmpTrapOID.0` for a v2c trap/inform,
+    /// or the RFC 3584 mapping of a v1 trap's enterprise/generic/specific
+    /// fields onto the same OID space.
+    pub trap_oid: Oid,
+    /// `sysUpTime.0` at the time the notification was generated.
+    pub sys_up_time: u32,
+    /// The v1 `generic-trap` field, or `None` for a v2c trap/inform.
+    pub generic_trap: Option<u32>,
+    /// The v1 `specific-trap` field, or `None` for a v2c trap/inform.
+    pub specific_trap: Option<u32>,
+    /// The UDP address the notification was sent from.
+    pub source: SocketAddr,
+    /// The notification's variable bindings, in wire order.
+    pub varbinds: Vec<(Oid, Value)>,
+}
+
+/// Errors receiving or parsing a trap/inform datagram.
+#[derive(Debug)]
+pub enum TrapError {
+    Io(io::Error),
+    Ber(BerError),
+    /// The datagram parsed as valid BER but not as a well-formed SNMP
+    /// message (wrong PDU tag, missing fields, wrong field types, ...).
+    Malformed(String),
+}
+
+impl fmt::Display for TrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrapError::Io(e) => write!(f, "trap listener I/O error: {e}"),
+            TrapError::Ber(e) => write!(f, "malformed trap BER encoding: {e}"),
+            TrapError::Malformed(s) => write!(f, "malformed trap message: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for TrapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TrapError::Io(e) => Some(e),
+            TrapError::Ber(e) => Some(e),
+            TrapError::Malformed(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for TrapError {
+    fn from(e: io::Error) -> Self {
+        TrapError::Io(e)
+    }
+}
+
+impl From<BerError> for TrapError {
+    fn from(e: BerError) -> Self {
+        TrapError::Ber(e)
+    }
+}
+
+/// A bound UDP socket receiving SNMP traps and informs.
+///
+/// Iterating yields one [`TrapEvent`] per received datagram, blocking
+/// between them; a datagram that fails to parse yields `Err` without
+/// ending the iteration, so one malformed packet can't wedge the listener.
+pub struct TrapListener {
+    socket: UdpSocket,
+}
+
+impl TrapListener {
+    /// Binds a UDP socket to `addr` (conventionally `0.0.0.0:162`, the
+    /// well-known SNMP trap port, or an ephemeral port for testing).
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr)?,
+        })
+    }
+
+    /// The address this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Blocks for the next trap/inform datagram, parses it, and (for an
+    /// InformRequest-PDU) sends the acknowledgement Response-PDU back to
+    /// the sender before returning.
+    pub fn recv(&self) -> Result<TrapEvent, TrapError> {
+        let mut buf = [0u8; 65536];
+        let (len, source) = self.socket.recv_from(&mut buf)?;
+        self.handle_datagram(&buf[..len], source)
+    }
+
+    fn handle_datagram(&self, datagram: &[u8], source: SocketAddr) -> Result<TrapEvent, TrapError> {
+        let mut cursor = Cursor::new(datagram);
+        let (tag, content) = read_tlv(&mut cursor)?;
+        if tag != TAG_SEQUENCE {
+            return Err(TrapError::Malformed(format!(
+                "expected message SEQUENCE tag {TAG_SEQUENCE:#04x}, got {tag:#04x}"
+            )));
+        }
+
+        let mut message = Cursor::new(content.as_slice());
+        let version = Value::decode_ber(&mut message)?
+            .as_integer()
+            .ok_or_else(|| TrapError::Malformed("version is not an INTEGER".to_string()))?;
+        let community = Value::decode_ber(&mut message)?
+            .as_octet_string()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| TrapError::Malformed("community is not an OCTET STRING".to_string()))?;
+
+        let (pdu_tag, pdu_content) = read_tlv(&mut message)?;
+        let mut pdu = Cursor::new(pdu_content.as_slice());
+
+        match pdu_tag {
+            PDU_TRAP_V1 => parse_v1_trap(&mut pdu, source),
+            PDU_TRAP_V2 => parse_v2_notification(&mut pdu, source),
+            PDU_INFORM_REQUEST => {
+                let (event, request_id) = parse_v2_notification_with_request_id(&mut pdu, source)?;
+                let ack = build_response_message(version, &community, request_id, &event.varbinds);
+                self.socket.send_to(&ack, source)?;
+                Ok(event)
+            }
+            other => Err(TrapError::Malformed(format!(
+                "unexpected PDU tag {other:#04x} (expected a Trap-PDU or InformRequest-PDU)"
+            ))),
+        }
+    }
+}
+
+impl Iterator for TrapListener {
+    type Item = Result<TrapEvent, TrapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv())
+    }
+}
+
+fn parse_v1_trap(pdu: &mut Cursor<&[u8]>, source: SocketAddr) -> Result<TrapEvent, TrapError> {
+    let enterprise = Value::decode_ber(pdu)?.as_oid().cloned().ok_or_else(|| {
+        TrapError::Malformed("enterprise is not an OBJECT IDENTIFIER".to_string())
+    })?;
+    Value::decode_ber(pdu)?; // agent-addr: not surfaced on TrapEvent, the real UDP source is.
+    let generic_trap = Value::decode_ber(pdu)?
+        .as_integer()
+        .ok_or_else(|| TrapError::Malformed("generic-trap is not an INTEGER".to_string()))?;
+    let specific_trap = Value::decode_ber(pdu)?
+        .as_integer()
+        .ok_or_else(|| TrapError::Malformed("specific-trap is not an INTEGER".to_string()))?;
+    let sys_up_time = match Value::decode_ber(pdu)? {
+        Value::TimeTicks(v) => v,
+        _ => {
+            return Err(TrapError::Malformed(
+                "time-stamp is not TimeTicks".to_string(),
+            ))
+        }
+    };
+    let varbinds = parse_varbind_list(pdu)?;
+
+    Ok(TrapEvent {
+        trap_oid: v1_trap_oid(&enterprise, generic_trap, specific_trap),
+        sys_up_time,
+        generic_trap: Some(generic_trap as u32),
+        specific_trap: Some(specific_trap as u32),
+        source,
+        varbinds,
+    })
+}
+
+/// Maps a v1 trap's enterprise/generic/specific fields onto the v2c
+/// `snmpTrapOID.0` OID space, per RFC 3584 section 3.2: the six standard
+/// generic traps map to fixed OIDs under `snmpTraps`, while an
+/// enterprise-specific trap (`generic-trap == 6`) maps to
+/// `enterprise.0.specific-trap`.
+fn v1_trap_oid(enterprise: &Oid, generic_trap: i32, specific_trap: i32) -> Oid {
+    const ENTERPRISE_SPECIFIC: i32 = 6;
+    if generic_trap != ENTERPRISE_SPECIFIC {
+        if let Some(parts) = usize::try_from(generic_trap)
+            .ok()
+            .and_then(|i| GENERIC_TRAP_OIDS.get(i))
+        {
+            return Oid::from_slice(parts).expect("generic trap OIDs are never empty");
+        }
+    }
+    enterprise.child(0).child(specific_trap as u32)
+}
+
+fn parse_v2_notification(
+    pdu: &mut Cursor<&[u8]>,
+    source: SocketAddr,
+) -> Result<TrapEvent, TrapError> {
+    Ok(parse_v2_notification_with_request_id(pdu, source)?.0)
+}
+
+fn parse_v2_notification_with_request_id(
+    pdu: &mut Cursor<&[u8]>,
+    source: SocketAddr,
+) -> Result<(TrapEvent, i32), TrapError> {
+    let request_id = Value::decode_ber(pdu)?
+        .as_integer()
+        .ok_or_else(|| TrapError::Malformed("request-id is not an INTEGER".to_string()))?;
+    Value::decode_ber(pdu)?; // error-status
+    Value::decode_ber(pdu)?; // error-index
+    let varbinds = parse_varbind_list(pdu)?;
+
+    // RFC 3416 section 4.2.6/4.2.7: the first two varbinds of a v2c
+    // trap/inform are always sysUpTime.0 then snmpTrapOID.0.
+    let sys_up_time = match varbinds.first() {
+        Some((_, Value::TimeTicks(v))) => *v,
+        _ => {
+            return Err(TrapError::Malformed(
+                "first varbind is not sysUpTime (TimeTicks)".to_string(),
+            ))
+        }
+    };
+    let trap_oid = match varbinds.get(1) {
+        Some((_, Value::ObjectIdentifier(o))) => o.clone(),
+        _ => {
+            return Err(TrapError::Malformed(
+                "second varbind is not snmpTrapOID (OBJECT IDENTIFIER)".to_string(),
+            ))
+        }
+    };
+
+    Ok((
+        TrapEvent {
+            trap_oid,
+            sys_up_time,
+            generic_trap: None,
+            specific_trap: None,
+            source,
+            varbinds,
+        },
+        request_id,
+    ))
+}
+
+fn parse_varbind_list(cursor: &mut Cursor<&[u8]>) -> Result<Vec<(Oid, Value)>, TrapError> {
+    let (tag, content) = read_tlv(cursor)?;
+    if tag != TAG_SEQUENCE {
+        return Err(TrapError::Malformed(format!(
+            "expected VarBindList SEQUENCE tag {TAG_SEQUENCE:#04x}, got {tag:#04x}"
+        )));
+    }
+
+    let mut list = Cursor::new(content.as_slice());
+    let mut varbinds = Vec::new();
+    while (list.position() as usize) < content.len() {
+        let (vb_tag, vb_content) = read_tlv(&mut list)?;
+        if vb_tag != TAG_SEQUENCE {
+            return Err(TrapError::Malformed(format!(
+                "expected VarBind SEQUENCE tag {TAG_SEQUENCE:#04x}, got {vb_tag:#04x}"
+            )));
+        }
+        let mut vb = Cursor::new(vb_content.as_slice());
+        let name = Value::decode_ber(&mut vb)?
+            .as_oid()
+            .cloned()
+            .ok_or_else(|| {
+                TrapError::Malformed("varbind name is not an OBJECT IDENTIFIER".to_string())
+            })?;
+        let value = Value::decode_ber(&mut vb)?;
+        varbinds.push((name, value));
+    }
+    Ok(varbinds)
+}
+
+fn encode_tlv(buf: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    crate::ber::encode_tlv(buf, tag, content)
+}
+
+/// Reads one BER TLV, returning its tag and content bytes. Bounds-checks
+/// the decoded length against the bytes remaining in `cursor` before
+/// allocating (see [`crate::ber::read_tlv`]) — this is the direct,
+/// unauthenticated network entry point, so a crafted length field must
+/// never drive an unbounded allocation here.
+fn read_tlv(cursor: &mut Cursor<&[u8]>) -> Result<(u8, Vec<u8>), TrapError> {
+    crate::ber::read_tlv(cursor).map_err(|e| TrapError::Malformed(e.to_string()))
+}
+
+fn encode_varbind_list(varbinds: &[(Oid, Value)]) -> Vec<u8> {
+    let mut list_content = Vec::new();
+    for (oid, value) in varbinds {
+        let mut vb_content = Vec::new();
+        Value::oid(oid.clone()).encode_ber(&mut vb_content);
+        value.encode_ber(&mut vb_content);
+        encode_tlv(&mut list_content, TAG_SEQUENCE, &vb_content);
+    }
+    let mut buf = Vec::new();
+    encode_tlv(&mut buf, TAG_SEQUENCE, &list_content);
+    buf
+}
+
+/// Builds a Response-PDU message acknowledging an InformRequest, per RFC
+/// 3416 section 4.2.7: same `request-id`, `error-status`/`error-index`
+/// zeroed, and the same variable bindings echoed back.
+fn build_response_message(
+    version: i32,
+    community: &[u8],
+    request_id: i32,
+    varbinds: &[(Oid, Value)],
+) -> Vec<u8> {
+    let mut pdu_content = Vec::new();
+    Value::integer(request_id).encode_ber(&mut pdu_content);
+    Value::integer(0).encode_ber(&mut pdu_content);
+    Value::integer(0).encode_ber(&mut pdu_content);
+    pdu_content.extend_from_slice(&encode_varbind_list(varbinds));
+
+    let mut message_content = Vec::new();
+    Value::integer(version).encode_ber(&mut message_content);
+    Value::octet_string(community.to_vec()).encode_ber(&mut message_content);
+    encode_tlv(&mut message_content, PDU_GET_RESPONSE, &pdu_content);
+
+    let mut buf = Vec::new();
+    encode_tlv(&mut buf, TAG_SEQUENCE, &message_content);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNMP_V1: i32 = 0;
+    const SNMP_V2C: i32 = 1;
+
+    fn encode_message(version: i32, community: &[u8], pdu_tag: u8, pdu_content: &[u8]) -> Vec<u8> {
+        let mut message_content = Vec::new();
+        Value::integer(version).encode_ber(&mut message_content);
+        Value::octet_string(community.to_vec()).encode_ber(&mut message_content);
+        encode_tlv(&mut message_content, pdu_tag, pdu_content);
+
+        let mut buf = Vec::new();
+        encode_tlv(&mut buf, TAG_SEQUENCE, &message_content);
+        buf
+    }
+
+    fn encode_v1_trap(
+        enterprise: &Oid,
+        agent_addr: std::net::Ipv4Addr,
+        generic_trap: i32,
+        specific_trap: i32,
+        time_stamp: u32,
+        varbinds: &[(Oid, Value)],
+    ) -> Vec<u8> {
+        let mut pdu_content = Vec::new();
+        Value::oid(enterprise.clone()).encode_ber(&mut pdu_content);
+        Value::ip_address(agent_addr).encode_ber(&mut pdu_content);
+        Value::integer(generic_trap).encode_ber(&mut pdu_content);
+        Value::integer(specific_trap).encode_ber(&mut pdu_content);
+        Value::timeticks(time_stamp).encode_ber(&mut pdu_content);
+        pdu_content.extend_from_slice(&encode_varbind_list(varbinds));
+        encode_message(SNMP_V1, b"public", PDU_TRAP_V1, &pdu_content)
+    }
+
+    fn encode_v2_notification(pdu_tag: u8, request_id: i32, varbinds: &[(Oid, Value)]) -> Vec<u8> {
+        let mut pdu_content = Vec::new();
+        Value::integer(request_id).encode_ber(&mut pdu_content);
+        Value::integer(0).encode_ber(&mut pdu_content);
+        Value::integer(0).encode_ber(&mut pdu_content);
+        pdu_content.extend_from_slice(&encode_varbind_list(varbinds));
+        encode_message(SNMP_V2C, b"public", pdu_tag, &pdu_content)
+    }
+
+    fn standard_v2_varbinds(sys_up_time: u32, trap_oid: &str) -> Vec<(Oid, Value)> {
+        vec![
+            (
+                "1.3.6.1.2.1.1.3.0".parse().unwrap(),
+                Value::timeticks(sys_up_time),
+            ),
+            (
+                "1.3.6.1.6.3.1.1.4.1.0".parse().unwrap(),
+                Value::oid(trap_oid.parse().unwrap()),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_parse_v1_trap_generic_link_down() {
+        let enterprise: Oid = "1.3.6.1.4.1.8072".parse().unwrap();
+        let varbinds = vec![("1.3.6.1.2.1.2.2.1.1.3".parse().unwrap(), Value::integer(3))];
+        let datagram = encode_v1_trap(
+            &enterprise,
+            std::net::Ipv4Addr::new(10, 0, 0, 1),
+            2, // linkDown
+            0,
+            12345,
+            &varbinds,
+        );
+
+        let listener = TrapListener::bind("127.0.0.1:0").unwrap();
+        let source: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        let event = listener.handle_datagram(&datagram, source).unwrap();
+
+        assert_eq!(event.trap_oid.to_string(), "1.3.6.1.6.3.1.1.5.3");
+        assert_eq!(event.sys_up_time, 12345);
+        assert_eq!(event.generic_trap, Some(2));
+        assert_eq!(event.specific_trap, Some(0));
+        assert_eq!(event.source, source);
+        assert_eq!(event.varbinds, varbinds);
+    }
+
+    #[test]
+    fn test_parse_v1_trap_enterprise_specific() {
+        let enterprise: Oid = "1.3.6.1.4.1.8072".parse().unwrap();
+        let datagram = encode_v1_trap(
+            &enterprise,
+            std::net::Ipv4Addr::new(10, 0, 0, 1),
+            6, // enterpriseSpecific
+            42,
+            99,
+            &[],
+        );
+
+        let listener = TrapListener::bind("127.0.0.1:0").unwrap();
+        let source: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        let event = listener.handle_datagram(&datagram, source).unwrap();
+
+        assert_eq!(event.trap_oid.to_string(), "1.3.6.1.4.1.8072.0.42");
+    }
+
+    #[test]
+    fn test_parse_v2_trap() {
+        let varbinds = standard_v2_varbinds(54321, "1.3.6.1.6.3.1.1.5.1");
+        let datagram = encode_v2_notification(PDU_TRAP_V2, 1, &varbinds);
+
+        let listener = TrapListener::bind("127.0.0.1:0").unwrap();
+        let source: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        let event = listener.handle_datagram(&datagram, source).unwrap();
+
+        assert_eq!(event.trap_oid.to_string(), "1.3.6.1.6.3.1.1.5.1");
+        assert_eq!(event.sys_up_time, 54321);
+        assert_eq!(event.generic_trap, None);
+        assert_eq!(event.specific_trap, None);
+        assert_eq!(event.varbinds, varbinds);
+    }
+
+    #[test]
+    fn test_inform_request_is_acknowledged() {
+        let varbinds = standard_v2_varbinds(111, "1.3.6.1.6.3.1.1.5.2");
+        let datagram = encode_v2_notification(PDU_INFORM_REQUEST, 7, &varbinds);
+
+        let listener = TrapListener::bind("127.0.0.1:0").unwrap();
+        let listener_addr = listener.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender_addr = sender.local_addr().unwrap();
+        sender.send_to(&datagram, listener_addr).unwrap();
+
+        let event = listener.recv().unwrap();
+        assert_eq!(event.trap_oid.to_string(), "1.3.6.1.6.3.1.1.5.2");
+
+        let mut ack_buf = [0u8; 4096];
+        let (ack_len, ack_from) = sender.recv_from(&mut ack_buf).unwrap();
+        assert_eq!(ack_from, listener_addr);
+
+        // The ack is a GetResponse-PDU with the same request-id and
+        // varbinds, per RFC 3416 section 4.2.7.
+        let mut cursor = Cursor::new(&ack_buf[..ack_len]);
+        let (tag, content) = read_tlv(&mut cursor).unwrap();
+        assert_eq!(tag, TAG_SEQUENCE);
+        let mut message = Cursor::new(content.as_slice());
+        Value::decode_ber(&mut message).unwrap(); // version
+        Value::decode_ber(&mut message).unwrap(); // community
+        let (pdu_tag, pdu_content) = read_tlv(&mut message).unwrap();
+        assert_eq!(pdu_tag, PDU_GET_RESPONSE);
+        let mut pdu = Cursor::new(pdu_content.as_slice());
+        let request_id = Value::decode_ber(&mut pdu).unwrap().as_integer().unwrap();
+        assert_eq!(request_id, 7);
+
+        let _ = sender_addr;
+    }
+
+    #[test]
+    fn test_unknown_pdu_tag_is_malformed() {
+        let datagram = encode_message(SNMP_V2C, b"public", 0xa1, &[]);
+        let listener = TrapListener::bind("127.0.0.1:0").unwrap();
+        let source: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        assert!(matches!(
+            listener.handle_datagram(&datagram, source),
+            Err(TrapError::Malformed(_))
+        ));
+    }
+}
@@ -133,17 +133,17 @@ impl fmt::Display for Value {
                     write!(f, "{v:?}")
                 }
             }
-            Value::Null() => write!(f, "NULL"),
+            Value::Null => write!(f, "NULL"),
             Value::ObjectIdentifier(o) => write!(f, "{o}"),
-            Value::IpAddress(a, b, c, d) => write!(f, "{a}.{b}.{c}.{d}"),
+            Value::IpAddress(addr) => write!(f, "{addr}"),
             Value::Counter32(v) => write!(f, "{v}"),
             Value::Gauge32(v) => write!(f, "{v}"),
             Value::TimeTicks(v) => write!(f, "{v}"),
             Value::Opaque(v) => write!(f, "{v:?}"),
             Value::Counter64(v) => write!(f, "{v}"),
-            Value::NoSuchObject() => write!(f, "noSuchObject"),
-            Value::NoSuchInstance() => write!(f, "noSuchInstance"),
-            Value::EndOfMibView() => write!(f, "endOfMibView"),
+            Value::NoSuchObject => write!(f, "noSuchObject"),
+            Value::NoSuchInstance => write!(f, "noSuchInstance"),
+            Value::EndOfMibView => write!(f, "endOfMibView"),
         }
     }
 }
@@ -184,6 +184,307 @@ impl From<Ipv4Addr> for Value {
     }
 }
 
+// BER/ASN.1 tags used on the SNMP wire.
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OBJECT_IDENTIFIER: u8 = 0x06;
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_TIME_TICKS: u8 = 0x43;
+const TAG_OPAQUE: u8 = 0x44;
+const TAG_COUNTER64: u8 = 0x46;
+const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+/// Errors that can occur while decoding a BER-encoded `Value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BerError {
+    UnexpectedEof,
+    UnknownTag(u8),
+    InvalidLength,
+    InvalidOid(String),
+}
+
+impl fmt::Display for BerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BerError::UnexpectedEof => write!(f, "unexpected end of BER input"),
+            BerError::UnknownTag(t) => write!(f, "unknown BER tag: {t:#04x}"),
+            BerError::InvalidLength => write!(f, "invalid BER length encoding"),
+            BerError::InvalidOid(s) => write!(f, "invalid BER-encoded OID: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for BerError {}
+
+impl From<crate::ber::FramingError> for BerError {
+    fn from(e: crate::ber::FramingError) -> Self {
+        match e {
+            crate::ber::FramingError::UnexpectedEof => BerError::UnexpectedEof,
+            crate::ber::FramingError::InvalidLength
+            | crate::ber::FramingError::LengthExceedsRemainingInput { .. } => {
+                BerError::InvalidLength
+            }
+        }
+    }
+}
+
+/// Encodes a BER length in definite short or long form.
+fn encode_length(buf: &mut Vec<u8>, len: usize) {
+    crate::ber::encode_length(buf, len)
+}
+
+/// Decodes a BER length in definite short or long form.
+fn decode_length(cursor: &mut std::io::Cursor<&[u8]>) -> Result<usize, BerError> {
+    crate::ber::decode_length(cursor).map_err(BerError::from)
+}
+
+/// Reads `len` bytes from `cursor`, bounds-checked against the bytes
+/// actually remaining so a crafted length field can never drive an
+/// unbounded allocation (see [`crate::ber::read_exact_bytes`]).
+fn read_exact_bytes(cursor: &mut std::io::Cursor<&[u8]>, len: usize) -> Result<Vec<u8>, BerError> {
+    crate::ber::read_exact_bytes(cursor, len).map_err(BerError::from)
+}
+
+/// Encodes `v` as the minimal two's-complement big-endian byte sequence,
+/// as required for BER INTEGER content.
+fn encode_signed_minimal(v: i64, buf: &mut Vec<u8>) {
+    let bytes = v.to_be_bytes();
+    let mut start = 0;
+    while start < bytes.len() - 1 {
+        let b = bytes[start];
+        let next_bit = (bytes[start + 1] & 0x80) != 0;
+        if (b == 0x00 && !next_bit) || (b == 0xff && next_bit) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+    buf.extend_from_slice(&bytes[start..]);
+}
+
+/// Encodes `v` as minimal BER INTEGER content, padding with a leading zero
+/// byte if needed so the unsigned value isn't misread as negative.
+fn encode_unsigned_minimal(v: u64, buf: &mut Vec<u8>) {
+    let bytes = v.to_be_bytes();
+    let mut start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+    if bytes[start] & 0x80 != 0 && start > 0 {
+        start -= 1;
+    } else if bytes[start] & 0x80 != 0 {
+        buf.push(0x00);
+    }
+    buf.extend_from_slice(&bytes[start..]);
+}
+
+fn decode_signed(bytes: &[u8]) -> i64 {
+    let mut v: i64 = if bytes.first().is_some_and(|&b| b & 0x80 != 0) {
+        -1
+    } else {
+        0
+    };
+    for &b in bytes {
+        v = (v << 8) | b as i64;
+    }
+    v
+}
+
+fn decode_unsigned(bytes: &[u8]) -> u64 {
+    let mut v: u64 = 0;
+    for &b in bytes {
+        v = (v << 8) | b as u64;
+    }
+    v
+}
+
+/// Encodes an OID's arcs using the standard `40*x + y` first-arc packing
+/// plus base-128 continuation for the rest.
+///
+/// This mirrors [`crate::oid::Oid::to_ber`]'s packing, but is kept as a
+/// separate encoder because `encode_ber` has no `Result` to report
+/// through and so can't reject an OID with fewer than two arcs the way
+/// `to_ber` does (see the panic below instead). Decoding, by contrast,
+/// has no such constraint, so [`decode_oid_arcs`] delegates straight to
+/// [`crate::oid::Oid::from_ber`] instead of keeping its own copy of that
+/// logic.
+///
+/// # Panics
+///
+/// Panics if `parts` has fewer than two arcs: a combined first arc needs
+/// both `x` and `y` (`40*x + y`), and silently encoding just `parts[0]*40`
+/// would be indistinguishable on the wire from a different, two-arc OID
+/// (e.g. `Oid::new(vec![5])` would round-trip as `2.120`). `Oid::new` only
+/// rejects an empty OID, so this is reachable from a legal `Value`; callers
+/// that can't guarantee two-or-more arcs should validate first.
+fn encode_oid_arcs(parts: &[u32], buf: &mut Vec<u8>) {
+    assert!(
+        parts.len() >= 2,
+        "at least two arcs are required to encode the combined first arc"
+    );
+    let first = parts[0] * 40 + parts[1];
+    encode_base128(first, buf);
+
+    for &part in &parts[2..] {
+        encode_base128(part, buf);
+    }
+}
+
+fn encode_base128(mut value: u32, buf: &mut Vec<u8>) {
+    let mut chunks = [0u8; 5];
+    let mut n = 0;
+    loop {
+        chunks[n] = (value & 0x7f) as u8;
+        value >>= 7;
+        n += 1;
+        if value == 0 {
+            break;
+        }
+    }
+    for (i, &chunk) in chunks[..n].iter().rev().enumerate() {
+        let continuation = if i == n - 1 { 0x00 } else { 0x80 };
+        buf.push(chunk | continuation);
+    }
+}
+
+/// Decodes an OID's arcs from BER content bytes by delegating to
+/// [`crate::oid::Oid::from_ber`], rather than keeping a second,
+/// independently-maintained decoder in this module. The first arc's
+/// `40*x + y` packing uses base-128 continuation bytes just like every
+/// other sub-identifier, so a naive single-byte read (as this function
+/// used to do) silently mis-decodes any OID whose packed first arc is
+/// `>= 128` (e.g. root arc 2 paired with a large second arc).
+fn decode_oid_arcs(bytes: &[u8]) -> Result<Vec<u32>, BerError> {
+    let oid = crate::oid::Oid::from_ber(bytes).map_err(|e| BerError::InvalidOid(e.to_string()))?;
+    Ok(oid.parts().to_vec())
+}
+
+impl Value {
+    /// Encodes this value as a BER TLV (tag-length-value) triple, appending
+    /// it to `buf`.
+    pub fn encode_ber(&self, buf: &mut Vec<u8>) {
+        match self {
+            Value::Integer(v) => {
+                let mut content = Vec::new();
+                encode_signed_minimal(*v as i64, &mut content);
+                buf.push(TAG_INTEGER);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::OctetString(v) => {
+                buf.push(TAG_OCTET_STRING);
+                encode_length(buf, v.len());
+                buf.extend_from_slice(v);
+            }
+            Value::Null => {
+                buf.push(TAG_NULL);
+                encode_length(buf, 0);
+            }
+            Value::ObjectIdentifier(o) => {
+                let mut content = Vec::new();
+                encode_oid_arcs(o.parts(), &mut content);
+                buf.push(TAG_OBJECT_IDENTIFIER);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::IpAddress(addr) => {
+                buf.push(TAG_IP_ADDRESS);
+                encode_length(buf, 4);
+                buf.extend_from_slice(&addr.octets());
+            }
+            Value::Counter32(v) => {
+                let mut content = Vec::new();
+                encode_unsigned_minimal(*v as u64, &mut content);
+                buf.push(TAG_COUNTER32);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::Gauge32(v) => {
+                let mut content = Vec::new();
+                encode_unsigned_minimal(*v as u64, &mut content);
+                buf.push(TAG_GAUGE32);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::TimeTicks(v) => {
+                let mut content = Vec::new();
+                encode_unsigned_minimal(*v as u64, &mut content);
+                buf.push(TAG_TIME_TICKS);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::Opaque(v) => {
+                buf.push(TAG_OPAQUE);
+                encode_length(buf, v.len());
+                buf.extend_from_slice(v);
+            }
+            Value::Counter64(v) => {
+                let mut content = Vec::new();
+                encode_unsigned_minimal(*v, &mut content);
+                buf.push(TAG_COUNTER64);
+                encode_length(buf, content.len());
+                buf.extend_from_slice(&content);
+            }
+            Value::NoSuchObject => {
+                buf.push(TAG_NO_SUCH_OBJECT);
+                encode_length(buf, 0);
+            }
+            Value::NoSuchInstance => {
+                buf.push(TAG_NO_SUCH_INSTANCE);
+                encode_length(buf, 0);
+            }
+            Value::EndOfMibView => {
+                buf.push(TAG_END_OF_MIB_VIEW);
+                encode_length(buf, 0);
+            }
+        }
+    }
+
+    /// Decodes a single BER TLV from `cursor`, advancing it past the value.
+    pub fn decode_ber(cursor: &mut std::io::Cursor<&[u8]>) -> Result<Value, BerError> {
+        use std::io::Read;
+
+        let mut tag_byte = [0u8; 1];
+        cursor
+            .read_exact(&mut tag_byte)
+            .map_err(|_| BerError::UnexpectedEof)?;
+        let tag = tag_byte[0];
+        let len = decode_length(cursor)?;
+        let content = read_exact_bytes(cursor, len)?;
+
+        match tag {
+            TAG_INTEGER => Ok(Value::Integer(decode_signed(&content) as i32)),
+            TAG_OCTET_STRING => Ok(Value::OctetString(content)),
+            TAG_NULL => Ok(Value::Null),
+            TAG_OBJECT_IDENTIFIER => {
+                let parts = decode_oid_arcs(&content)?;
+                let oid = Oid::new(parts)
+                    .map_err(|e| BerError::InvalidOid(e.to_string()))?;
+                Ok(Value::ObjectIdentifier(oid))
+            }
+            TAG_IP_ADDRESS => {
+                if content.len() != 4 {
+                    return Err(BerError::InvalidLength);
+                }
+                Ok(Value::IpAddress(Ipv4Addr::new(
+                    content[0], content[1], content[2], content[3],
+                )))
+            }
+            TAG_COUNTER32 => Ok(Value::Counter32(decode_unsigned(&content) as u32)),
+            TAG_GAUGE32 => Ok(Value::Gauge32(decode_unsigned(&content) as u32)),
+            TAG_TIME_TICKS => Ok(Value::TimeTicks(decode_unsigned(&content) as u32)),
+            TAG_OPAQUE => Ok(Value::Opaque(content)),
+            TAG_COUNTER64 => Ok(Value::Counter64(decode_unsigned(&content))),
+            TAG_NO_SUCH_OBJECT => Ok(Value::NoSuchObject),
+            TAG_NO_SUCH_INSTANCE => Ok(Value::NoSuchInstance),
+            TAG_END_OF_MIB_VIEW => Ok(Value::EndOfMibView),
+            other => Err(BerError::UnknownTag(other)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,4 +536,120 @@ mod tests {
         let v3: Value = Ipv4Addr::new(10, 0, 0, 1).into();
         assert_eq!(v3.to_string(), "10.0.0.1");
     }
+
+    fn ber_roundtrip(v: &Value) -> Value {
+        let mut buf = Vec::new();
+        v.encode_ber(&mut buf);
+        let mut cursor = std::io::Cursor::new(buf.as_slice());
+        Value::decode_ber(&mut cursor).unwrap()
+    }
+
+    #[test]
+    fn test_ber_integer_roundtrip() {
+        for v in [0, 1, -1, 127, 128, -128, -129, i32::MAX, i32::MIN] {
+            let value = Value::integer(v);
+            assert_eq!(ber_roundtrip(&value), value);
+        }
+    }
+
+    #[test]
+    fn test_ber_integer_minimal_encoding() {
+        let mut buf = Vec::new();
+        Value::integer(0).encode_ber(&mut buf);
+        assert_eq!(buf, vec![0x02, 0x01, 0x00]);
+
+        let mut buf = Vec::new();
+        Value::integer(128).encode_ber(&mut buf);
+        assert_eq!(buf, vec![0x02, 0x02, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_ber_octet_string_roundtrip() {
+        let value = Value::string("hello snmp");
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    fn test_ber_null_roundtrip() {
+        assert_eq!(ber_roundtrip(&Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn test_ber_oid_roundtrip() {
+        let oid: Oid = "1.3.6.1.4.1.12345.1".parse().unwrap();
+        let value = Value::oid(oid);
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    fn test_ber_oid_roundtrip_multi_byte_first_arc() {
+        // 40*2 + 999999 packs to a first "arc" >= 128, so it's BER-encoded
+        // across multiple base-128 bytes; a decoder that only reads one
+        // byte for the combined first arc mis-decodes this.
+        let oid: Oid = "2.999999.5".parse().unwrap();
+        let value = Value::oid(oid);
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two arcs are required")]
+    fn test_ber_oid_encode_panics_on_single_arc_oid() {
+        // A single-arc OID can't pack a combined `40*x + y` first arc, so
+        // encoding it would silently produce a different, valid-looking
+        // two-arc OID on the wire instead.
+        let oid = Oid::new(vec![5]).unwrap();
+        let value = Value::oid(oid);
+        let mut buf = Vec::new();
+        value.encode_ber(&mut buf);
+    }
+
+    #[test]
+    fn test_ber_ip_address_roundtrip() {
+        let value = Value::ip_address(Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    fn test_ber_counters_and_gauges_roundtrip() {
+        for value in [
+            Value::counter32(u32::MAX),
+            Value::gauge32(0),
+            Value::timeticks(123456),
+            Value::counter64(u64::MAX),
+        ] {
+            assert_eq!(ber_roundtrip(&value), value);
+        }
+    }
+
+    #[test]
+    fn test_ber_opaque_roundtrip() {
+        let value = Value::opaque(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    fn test_ber_exception_markers_roundtrip() {
+        for value in [Value::NoSuchObject, Value::NoSuchInstance, Value::EndOfMibView] {
+            assert_eq!(ber_roundtrip(&value), value);
+        }
+    }
+
+    #[test]
+    fn test_ber_long_form_length() {
+        let value = Value::octet_string(vec![0u8; 200]);
+        let mut buf = Vec::new();
+        value.encode_ber(&mut buf);
+        // 200 >= 0x80, so length is encoded in long form: 0x81 0xc8
+        assert_eq!(&buf[..3], &[0x04, 0x81, 0xc8]);
+        assert_eq!(ber_roundtrip(&value), value);
+    }
+
+    #[test]
+    fn test_ber_unknown_tag() {
+        let mut cursor = std::io::Cursor::new(&[0x99u8, 0x00][..]);
+        assert_eq!(
+            Value::decode_ber(&mut cursor),
+            Err(BerError::UnknownTag(0x99))
+        );
+    }
 }
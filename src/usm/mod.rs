@@ -0,0 +1,862 @@
+//! SNMPv3 User-based Security Model (RFC 3414): per-message authentication
+//! and privacy layered on top of the plain community-based access this
+//! crate otherwise speaks.
+//!
+//! A manager and agent first run engine discovery (an empty-engine-ID probe
+//! answered with the agent's `msgAuthoritativeEngineID`/boots/time), then
+//! localize each user's password into the authoritative engine's key space
+//! via [`localize_key`], and from then on authenticate every message with
+//! HMAC-MD5-96/HMAC-SHA-96 ([`authenticate`]/[`verify`]) and, if privacy is
+//! configured, encrypt the [`ScopedPdu`] with DES-CBC or AES-128-CFB128
+//! ([`encrypt_des`]/[`encrypt_aes128`]).
+
+use std::io::Cursor;
+
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use cfb_mode::cipher::AsyncStreamCipher;
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use sha1::Sha1;
+
+/// One password-to-key-and-expand cycle per RFC 3414 section A.2: repeat
+/// the password to fill 1 MiB, then hash it.
+const PASSWORD_EXPANSION_LEN: usize = 1_048_576;
+
+/// Authentication algorithms USM supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthProtocol {
+    HmacMd5,
+    HmacSha1,
+}
+
+impl AuthProtocol {
+    /// Length in bytes of the key this protocol's digest produces (and
+    /// therefore of the localized key derived from it).
+    pub fn key_len(self) -> usize {
+        match self {
+            AuthProtocol::HmacMd5 => 16,
+            AuthProtocol::HmacSha1 => 20,
+        }
+    }
+}
+
+/// Privacy (encryption) algorithms USM supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivProtocol {
+    Des,
+    Aes128,
+}
+
+impl PrivProtocol {
+    /// Length in bytes of localized key material this protocol consumes:
+    /// 8 for the DES key plus 8 for its pre-IV (RFC 3414 8.1.1.1), or 16
+    /// for the AES-128 key alone (RFC 3826, whose IV comes entirely from
+    /// engine boots/time and the privacy parameters instead).
+    pub fn key_len(self) -> usize {
+        16
+    }
+}
+
+/// Errors from key derivation, authentication, or encryption/decryption.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsmError {
+    /// Localized key material is shorter than the protocol needs.
+    KeyTooShort { needed: usize, got: usize },
+    /// HMAC verification failed: wrong key, tampered message, or both ends
+    /// disagree on the authoritative engine.
+    AuthenticationFailed,
+    /// Block-cipher decryption failed (bad padding, wrong key, or truncated
+    /// ciphertext).
+    DecryptionFailed,
+    /// Malformed BER while decoding a [`SecurityParameters`] or
+    /// [`ScopedPdu`].
+    InvalidBer(String),
+}
+
+impl std::fmt::Display for UsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsmError::KeyTooShort { needed, got } => {
+                write!(f, "key too short: need {needed} bytes, got {got}")
+            }
+            UsmError::AuthenticationFailed => write!(f, "USM authentication failed"),
+            UsmError::DecryptionFailed => write!(f, "USM decryption failed"),
+            UsmError::InvalidBer(s) => write!(f, "invalid USM BER encoding: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for UsmError {}
+
+/// A configured SNMPv3 user: which hash authenticates its messages and
+/// which cipher encrypts them, plus the already-localized keys for both.
+/// Use [`localize_key`] to derive `auth_key`/`priv_key` from a password and
+/// the authoritative engine ID discovered via [`SecurityParameters`].
+#[derive(Debug, Clone)]
+pub struct UsmUser {
+    pub name: String,
+    pub auth_protocol: Option<AuthProtocol>,
+    pub auth_key: Vec<u8>,
+    pub priv_protocol: Option<PrivProtocol>,
+    pub priv_key: Vec<u8>,
+}
+
+/// Expands `password` to `PASSWORD_EXPANSION_LEN` bytes by repetition and
+/// hashes it, producing the non-localized key `Ku` (RFC 3414 section A.2).
+pub fn password_to_key(password: &[u8], protocol: AuthProtocol) -> Vec<u8> {
+    debug_assert!(!password.is_empty(), "password must be non-empty");
+
+    let mut chunk = [0u8; 64];
+    let mut produced = 0usize;
+
+    match protocol {
+        AuthProtocol::HmacMd5 => {
+            let mut hasher = Md5::new();
+            while produced < PASSWORD_EXPANSION_LEN {
+                for (i, b) in chunk.iter_mut().enumerate() {
+                    *b = password[(produced + i) % password.len()];
+                }
+                hasher.update(chunk);
+                produced += chunk.len();
+            }
+            hasher.finalize().to_vec()
+        }
+        AuthProtocol::HmacSha1 => {
+            let mut hasher = Sha1::new();
+            while produced < PASSWORD_EXPANSION_LEN {
+                for (i, b) in chunk.iter_mut().enumerate() {
+                    *b = password[(produced + i) % password.len()];
+                }
+                hasher.update(chunk);
+                produced += chunk.len();
+            }
+            hasher.finalize().to_vec()
+        }
+    }
+}
+
+/// Localizes `Ku` (see [`password_to_key`]) to `engine_id` via
+/// `H(Ku || engineID || Ku)` (RFC 3414 section 2.6), so the same password
+/// yields different per-agent keys.
+pub fn localize_key(ku: &[u8], engine_id: &[u8], protocol: AuthProtocol) -> Vec<u8> {
+    match protocol {
+        AuthProtocol::HmacMd5 => {
+            let mut hasher = Md5::new();
+            hasher.update(ku);
+            hasher.update(engine_id);
+            hasher.update(ku);
+            hasher.finalize().to_vec()
+        }
+        AuthProtocol::HmacSha1 => {
+            let mut hasher = Sha1::new();
+            hasher.update(ku);
+            hasher.update(engine_id);
+            hasher.update(ku);
+            hasher.finalize().to_vec()
+        }
+    }
+}
+
+type HmacMd5 = Hmac<Md5>;
+type HmacSha1 = Hmac<Sha1>;
+
+/// Computes HMAC-MD5-96/HMAC-SHA-96 (RFC 3414 section 6.3) over `message`,
+/// which must have its 12-byte `msgAuthenticationParameters` field already
+/// zeroed; the caller back-patches the real digest in after this returns.
+pub fn authenticate(key: &[u8], protocol: AuthProtocol, message: &[u8]) -> Vec<u8> {
+    match protocol {
+        AuthProtocol::HmacMd5 => {
+            let mut mac = HmacMd5::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(message);
+            mac.finalize().into_bytes()[..12].to_vec()
+        }
+        AuthProtocol::HmacSha1 => {
+            let mut mac = HmacSha1::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(message);
+            mac.finalize().into_bytes()[..12].to_vec()
+        }
+    }
+}
+
+/// Verifies a received `digest` against `message` (with
+/// `msgAuthenticationParameters` zeroed, as in [`authenticate`]), using a
+/// constant-time comparison so timing can't leak how many leading bytes
+/// matched.
+pub fn verify(key: &[u8], protocol: AuthProtocol, message: &[u8], digest: &[u8]) -> bool {
+    let expected = authenticate(key, protocol, message);
+    if expected.len() != digest.len() {
+        return false;
+    }
+    expected
+        .iter()
+        .zip(digest.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+type DesCbcEnc = cbc::Encryptor<des::Des>;
+type DesCbcDec = cbc::Decryptor<des::Des>;
+type Aes128CfbEnc = cfb_mode::Encryptor<aes::Aes128>;
+type Aes128CfbDec = cfb_mode::Decryptor<aes::Aes128>;
+
+/// Derives the 8-byte `msgPrivacyParameters` DES salt from the engine's
+/// reboot count and a strictly-increasing per-message local counter (RFC
+/// 3414 section 8.1.1.1).
+pub fn des_privacy_parameters(engine_boots: u32, local_counter: u32) -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&engine_boots.to_be_bytes());
+    out[4..].copy_from_slice(&local_counter.to_be_bytes());
+    out
+}
+
+fn des_iv(priv_key: &[u8], privacy_parameters: &[u8; 8]) -> Result<[u8; 8], UsmError> {
+    if priv_key.len() < 16 {
+        return Err(UsmError::KeyTooShort {
+            needed: 16,
+            got: priv_key.len(),
+        });
+    }
+    // Bytes 8..16 of the localized privacy key are the pre-IV, XORed with
+    // the per-message salt to produce the actual IV.
+    let mut iv = [0u8; 8];
+    for i in 0..8 {
+        iv[i] = priv_key[8 + i] ^ privacy_parameters[i];
+    }
+    Ok(iv)
+}
+
+/// Encrypts `plaintext` (a [`ScopedPdu`]'s encoded bytes) with DES-CBC,
+/// returning the ciphertext and the `msgPrivacyParameters` to send
+/// alongside it.
+pub fn encrypt_des(
+    priv_key: &[u8],
+    engine_boots: u32,
+    local_counter: u32,
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, [u8; 8]), UsmError> {
+    if priv_key.len() < 16 {
+        return Err(UsmError::KeyTooShort {
+            needed: 16,
+            got: priv_key.len(),
+        });
+    }
+    let privacy_parameters = des_privacy_parameters(engine_boots, local_counter);
+    let iv = des_iv(priv_key, &privacy_parameters)?;
+
+    let pad = 8 - (plaintext.len() % 8);
+    let mut buf = plaintext.to_vec();
+    buf.resize(plaintext.len() + pad, 0);
+
+    let key: [u8; 8] = priv_key[..8].try_into().expect("checked length above");
+    let ciphertext = DesCbcEnc::new(&key.into(), &iv.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, plaintext.len())
+        .map_err(|_| UsmError::DecryptionFailed)?
+        .to_vec();
+
+    Ok((ciphertext, privacy_parameters))
+}
+
+/// Decrypts a DES-CBC ciphertext produced by [`encrypt_des`].
+pub fn decrypt_des(
+    priv_key: &[u8],
+    privacy_parameters: &[u8; 8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, UsmError> {
+    if priv_key.len() < 16 {
+        return Err(UsmError::KeyTooShort {
+            needed: 16,
+            got: priv_key.len(),
+        });
+    }
+    let iv = des_iv(priv_key, privacy_parameters)?;
+    let key: [u8; 8] = priv_key[..8].try_into().expect("checked length above");
+
+    let mut buf = ciphertext.to_vec();
+    DesCbcDec::new(&key.into(), &iv.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .map(|pt| pt.to_vec())
+        .map_err(|_| UsmError::DecryptionFailed)
+}
+
+/// Derives the 8-byte `msgPrivacyParameters` AES-128 salt from a strictly
+/// increasing per-message local counter (RFC 3826 section 3.1.2.1).
+pub fn aes128_privacy_parameters(local_counter: u64) -> [u8; 8] {
+    local_counter.to_be_bytes()
+}
+
+fn aes128_iv(engine_boots: u32, engine_time: u32, privacy_parameters: &[u8; 8]) -> [u8; 16] {
+    let mut iv = [0u8; 16];
+    iv[..4].copy_from_slice(&engine_boots.to_be_bytes());
+    iv[4..8].copy_from_slice(&engine_time.to_be_bytes());
+    iv[8..].copy_from_slice(privacy_parameters);
+    iv
+}
+
+/// Encrypts `plaintext` (a [`ScopedPdu`]'s encoded bytes) with
+/// AES-128-CFB128, returning the ciphertext and the `msgPrivacyParameters`
+/// to send alongside it. Unlike DES, the IV is built directly from the
+/// engine's boots/time and the salt rather than XORed with key material.
+pub fn encrypt_aes128(
+    priv_key: &[u8],
+    engine_boots: u32,
+    engine_time: u32,
+    local_counter: u64,
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, [u8; 8]), UsmError> {
+    if priv_key.len() < 16 {
+        return Err(UsmError::KeyTooShort {
+            needed: 16,
+            got: priv_key.len(),
+        });
+    }
+    let privacy_parameters = aes128_privacy_parameters(local_counter);
+    let iv = aes128_iv(engine_boots, engine_time, &privacy_parameters);
+    let key: [u8; 16] = priv_key[..16].try_into().expect("checked length above");
+
+    let mut buf = plaintext.to_vec();
+    Aes128CfbEnc::new(&key.into(), &iv.into()).encrypt(&mut buf);
+    Ok((buf, privacy_parameters))
+}
+
+/// Decrypts an AES-128-CFB128 ciphertext produced by [`encrypt_aes128`].
+pub fn decrypt_aes128(
+    priv_key: &[u8],
+    engine_boots: u32,
+    engine_time: u32,
+    privacy_parameters: &[u8; 8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, UsmError> {
+    if priv_key.len() < 16 {
+        return Err(UsmError::KeyTooShort {
+            needed: 16,
+            got: priv_key.len(),
+        });
+    }
+    let iv = aes128_iv(engine_boots, engine_time, privacy_parameters);
+    let key: [u8; 16] = priv_key[..16].try_into().expect("checked length above");
+
+    let mut buf = ciphertext.to_vec();
+    Aes128CfbDec::new(&key.into(), &iv.into()).decrypt(&mut buf);
+    Ok(buf)
+}
+
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+
+fn encode_length(buf: &mut Vec<u8>, len: usize) {
+    crate::ber::encode_length(buf, len)
+}
+
+/// Reads one BER TLV, returning its tag and content bytes. Bounds-checks
+/// the decoded length against the bytes remaining in `cursor` before
+/// allocating (see [`crate::ber::read_tlv`]).
+fn read_tlv(cursor: &mut Cursor<&[u8]>) -> Result<(u8, Vec<u8>), UsmError> {
+    crate::ber::read_tlv(cursor).map_err(|e| UsmError::InvalidBer(e.to_string()))
+}
+
+fn encode_octet_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.push(TAG_OCTET_STRING);
+    encode_length(buf, bytes.len());
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_unsigned_integer(buf: &mut Vec<u8>, value: u32) {
+    let bytes = value.to_be_bytes();
+    let mut significant = &bytes[bytes.iter().position(|&b| b != 0).unwrap_or(3)..];
+    if significant.is_empty() {
+        significant = &bytes[3..];
+    }
+    let mut content = Vec::with_capacity(significant.len() + 1);
+    // An unsigned value whose top bit is set needs a leading zero byte so
+    // it isn't misread as a negative BER INTEGER.
+    if significant[0] & 0x80 != 0 {
+        content.push(0);
+    }
+    content.extend_from_slice(significant);
+
+    buf.push(TAG_INTEGER);
+    encode_length(buf, content.len());
+    buf.extend_from_slice(&content);
+}
+
+fn decode_unsigned_integer(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32)
+}
+
+/// `UsmSecurityParameters` (RFC 3414 section 2.4): identifies the
+/// authoritative engine and user a message is secured against, and carries
+/// the per-message authentication/privacy tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecurityParameters {
+    pub authoritative_engine_id: Vec<u8>,
+    pub authoritative_engine_boots: u32,
+    pub authoritative_engine_time: u32,
+    pub user_name: String,
+    pub authentication_parameters: Vec<u8>,
+    pub privacy_parameters: Vec<u8>,
+}
+
+impl SecurityParameters {
+    /// An engine-discovery probe: every field empty/zero, per RFC 3414
+    /// section 4. The agent's reply carries the real
+    /// `authoritative_engine_id`/boots/time in its own `SecurityParameters`.
+    pub fn discovery_probe() -> Self {
+        Self::default()
+    }
+
+    /// BER-encodes a [`Self::discovery_probe`], ready to embed as the
+    /// `msgSecurityParameters` of an unauthenticated discovery request.
+    /// Sending it (and receiving the agent's reply) is still up to the
+    /// caller, since this crate has no SNMPv3 message-envelope codec to
+    /// carry it in - see [`Self::from_discovery_reply`] for the other half
+    /// of the round trip.
+    pub fn encode_discovery_probe() -> Vec<u8> {
+        let mut buf = Vec::new();
+        Self::discovery_probe().encode(&mut buf);
+        buf
+    }
+
+    /// Parses the `msgSecurityParameters` of an agent's reply to a
+    /// discovery probe, returning the `authoritative_engine_id`/boots/time
+    /// it learned. `user_name`/`authentication_parameters`/
+    /// `privacy_parameters` are empty in a discovery reply and are ignored.
+    pub fn from_discovery_reply(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), UsmError> {
+        let params = Self::decode(bytes)?;
+        Ok((
+            params.authoritative_engine_id,
+            params.authoritative_engine_boots,
+            params.authoritative_engine_time,
+        ))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        let mut content = Vec::new();
+        encode_octet_string(&mut content, &self.authoritative_engine_id);
+        encode_unsigned_integer(&mut content, self.authoritative_engine_boots);
+        encode_unsigned_integer(&mut content, self.authoritative_engine_time);
+        encode_octet_string(&mut content, self.user_name.as_bytes());
+        encode_octet_string(&mut content, &self.authentication_parameters);
+        encode_octet_string(&mut content, &self.privacy_parameters);
+
+        buf.push(TAG_SEQUENCE);
+        encode_length(buf, content.len());
+        buf.extend_from_slice(&content);
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, UsmError> {
+        let mut cursor = Cursor::new(bytes);
+        let (tag, content) = read_tlv(&mut cursor)?;
+        if tag != TAG_SEQUENCE {
+            return Err(UsmError::InvalidBer(format!(
+                "expected SEQUENCE tag {TAG_SEQUENCE:#04x}, got {tag:#04x}"
+            )));
+        }
+
+        let mut inner = Cursor::new(content.as_slice());
+        let (_, authoritative_engine_id) = read_tlv(&mut inner)?;
+        let (_, boots) = read_tlv(&mut inner)?;
+        let (_, time) = read_tlv(&mut inner)?;
+        let (_, user_name) = read_tlv(&mut inner)?;
+        let (_, authentication_parameters) = read_tlv(&mut inner)?;
+        let (_, privacy_parameters) = read_tlv(&mut inner)?;
+
+        Ok(Self {
+            authoritative_engine_id,
+            authoritative_engine_boots: decode_unsigned_integer(&boots),
+            authoritative_engine_time: decode_unsigned_integer(&time),
+            user_name: String::from_utf8(user_name)
+                .map_err(|_| UsmError::InvalidBer("user name is not valid UTF-8".to_string()))?,
+            authentication_parameters,
+            privacy_parameters,
+        })
+    }
+}
+
+/// `ScopedPDU` (RFC 3414 section 2.3): the plaintext a USM message's
+/// privacy layer encrypts, wrapping the real PDU with the context it
+/// applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopedPdu {
+    pub context_engine_id: Vec<u8>,
+    pub context_name: Vec<u8>,
+    /// The already BER-encoded PDU (e.g. from `Value::encode_ber` applied
+    /// to each varbind and assembled into a PDU sequence).
+    pub pdu: Vec<u8>,
+}
+
+impl ScopedPdu {
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        let mut content = Vec::new();
+        encode_octet_string(&mut content, &self.context_engine_id);
+        encode_octet_string(&mut content, &self.context_name);
+        content.extend_from_slice(&self.pdu);
+
+        buf.push(TAG_SEQUENCE);
+        encode_length(buf, content.len());
+        buf.extend_from_slice(&content);
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, UsmError> {
+        let mut cursor = Cursor::new(bytes);
+        let (tag, content) = read_tlv(&mut cursor)?;
+        if tag != TAG_SEQUENCE {
+            return Err(UsmError::InvalidBer(format!(
+                "expected SEQUENCE tag {TAG_SEQUENCE:#04x}, got {tag:#04x}"
+            )));
+        }
+
+        let mut inner = Cursor::new(content.as_slice());
+        let (_, context_engine_id) = read_tlv(&mut inner)?;
+        let (_, context_name) = read_tlv(&mut inner)?;
+        let pdu_start = inner.position() as usize;
+        let pdu = content[pdu_start..].to_vec();
+
+        Ok(Self {
+            context_engine_id,
+            context_name,
+            pdu,
+        })
+    }
+}
+
+/// A ciphertext produced by [`UsmSession::encrypt`] together with the
+/// `msgPrivacyParameters` it was encrypted under.
+type EncryptedMessage = (Vec<u8>, Vec<u8>);
+
+/// Per-peer USM session state: a [`UsmUser`]'s localized keys plus the
+/// authoritative engine's boots/time and a strictly increasing privacy
+/// counter, threaded through successive [`authenticate`]/[`verify`] and
+/// [`encrypt`](Self::encrypt)/[`decrypt`](Self::decrypt) calls.
+///
+/// Building one requires already knowing the authoritative engine's
+/// `engine_id`/`engine_boots`/`engine_time` - in practice learned once via
+/// an engine-discovery probe/reply exchange (RFC 3414 section 4) before
+/// the first authenticated message. [`SecurityParameters::encode_discovery_probe`]
+/// and [`Self::from_discovery_reply`] cover the USM half of that exchange
+/// (building the probe's `msgSecurityParameters` and parsing the reply's);
+/// actually sending and receiving those bytes is still up to the caller,
+/// since this crate has no general SNMPv3 message envelope codec to carry
+/// them in.
+pub struct UsmSession {
+    pub user: UsmUser,
+    pub engine_id: Vec<u8>,
+    pub engine_boots: u32,
+    pub engine_time: u32,
+    local_counter: u64,
+}
+
+impl UsmSession {
+    /// Builds a session for `user` against an already-discovered
+    /// authoritative engine.
+    pub fn new(user: UsmUser, engine_id: Vec<u8>, engine_boots: u32, engine_time: u32) -> Self {
+        Self {
+            user,
+            engine_id,
+            engine_boots,
+            engine_time,
+            local_counter: 0,
+        }
+    }
+
+    /// Completes an engine-discovery round trip: given the
+    /// `msgSecurityParameters` bytes from an agent's reply to a
+    /// [`SecurityParameters::encode_discovery_probe`] request, parses out
+    /// the authoritative engine's identity and builds a session against it.
+    pub fn from_discovery_reply(
+        user: UsmUser,
+        reply_security_parameters: &[u8],
+    ) -> Result<Self, UsmError> {
+        let (engine_id, engine_boots, engine_time) =
+            SecurityParameters::from_discovery_reply(reply_security_parameters)?;
+        Ok(Self::new(user, engine_id, engine_boots, engine_time))
+    }
+
+    /// Authenticates `message` under this session's auth key, or returns an
+    /// empty digest if the user has no auth protocol configured.
+    pub fn authenticate(&self, message: &[u8]) -> Vec<u8> {
+        match self.user.auth_protocol {
+            Some(protocol) => authenticate(&self.user.auth_key, protocol, message),
+            None => Vec::new(),
+        }
+    }
+
+    /// Verifies `digest` against `message` under this session's auth key.
+    /// A user with no auth protocol configured never verifies.
+    pub fn verify(&self, message: &[u8], digest: &[u8]) -> bool {
+        match self.user.auth_protocol {
+            Some(protocol) => verify(&self.user.auth_key, protocol, message, digest),
+            None => false,
+        }
+    }
+
+    /// Encrypts a [`ScopedPdu`]'s encoded bytes under this session's
+    /// privacy protocol, advancing the privacy counter so the next call
+    /// never reuses a salt/IV. Returns `None` if the user has no privacy
+    /// protocol configured.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Option<Result<EncryptedMessage, UsmError>> {
+        let protocol = self.user.priv_protocol?;
+        let counter = self.local_counter;
+        self.local_counter += 1;
+
+        Some(match protocol {
+            PrivProtocol::Des => encrypt_des(
+                &self.user.priv_key,
+                self.engine_boots,
+                counter as u32,
+                plaintext,
+            )
+            .map(|(ciphertext, privacy_parameters)| (ciphertext, privacy_parameters.to_vec())),
+            PrivProtocol::Aes128 => encrypt_aes128(
+                &self.user.priv_key,
+                self.engine_boots,
+                self.engine_time,
+                counter,
+                plaintext,
+            )
+            .map(|(ciphertext, privacy_parameters)| (ciphertext, privacy_parameters.to_vec())),
+        })
+    }
+
+    /// Decrypts a ciphertext produced by [`Self::encrypt`] (by this session
+    /// or its peer), given the `msgPrivacyParameters` it was sent with.
+    /// Returns `None` if the user has no privacy protocol configured.
+    pub fn decrypt(
+        &self,
+        privacy_parameters: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Result<Vec<u8>, UsmError>> {
+        let protocol = self.user.priv_protocol?;
+        Some(match protocol {
+            PrivProtocol::Des => {
+                let privacy_parameters: [u8; 8] = match privacy_parameters.try_into() {
+                    Ok(p) => p,
+                    Err(_) => {
+                        return Some(Err(UsmError::InvalidBer(
+                            "DES privacy parameters must be 8 bytes".to_string(),
+                        )));
+                    }
+                };
+                decrypt_des(&self.user.priv_key, &privacy_parameters, ciphertext)
+            }
+            PrivProtocol::Aes128 => {
+                let privacy_parameters: [u8; 8] = match privacy_parameters.try_into() {
+                    Ok(p) => p,
+                    Err(_) => {
+                        return Some(Err(UsmError::InvalidBer(
+                            "AES-128 privacy parameters must be 8 bytes".to_string(),
+                        )));
+                    }
+                };
+                decrypt_aes128(
+                    &self.user.priv_key,
+                    self.engine_boots,
+                    self.engine_time,
+                    &privacy_parameters,
+                    ciphertext,
+                )
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    // RFC 3414 Appendix A.3.1: password-to-key with MD5.
+    #[test]
+    fn test_password_to_key_md5_rfc3414_vector() {
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacMd5);
+        assert_eq!(hex(&ku), "9faf3283884e92834ebc9847d8edd963");
+    }
+
+    // RFC 3414 Appendix A.3.2: password-to-key with SHA-1.
+    #[test]
+    fn test_password_to_key_sha1_rfc3414_vector() {
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacSha1);
+        assert_eq!(hex(&ku), "9fb5cc0381497b3793528939ff788d5d79145211");
+    }
+
+    // RFC 3414 Appendix A.4: localizing the MD5 key to engine ID
+    // 0000000000000000000002.
+    #[test]
+    fn test_localize_md5_rfc3414_vector() {
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacMd5);
+        let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+        let kul = localize_key(&ku, &engine_id, AuthProtocol::HmacMd5);
+        assert_eq!(hex(&kul), "526f5eed9fcce26f8964c2930787d82b");
+    }
+
+    // RFC 3414 Appendix A.4: localizing the SHA-1 key to the same engine ID.
+    #[test]
+    fn test_localize_sha1_rfc3414_vector() {
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacSha1);
+        let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+        let kul = localize_key(&ku, &engine_id, AuthProtocol::HmacSha1);
+        assert_eq!(hex(&kul), "6695febc9288e36282235fc7151f128497b38f3f");
+    }
+
+    #[test]
+    fn test_authenticate_and_verify_roundtrip() {
+        let key = vec![7u8; 20];
+        let mut message = b"a zeroed-auth-params SNMPv3 message".to_vec();
+        let digest = authenticate(&key, AuthProtocol::HmacSha1, &message);
+        assert_eq!(digest.len(), 12);
+        assert!(verify(&key, AuthProtocol::HmacSha1, &message, &digest));
+
+        message.push(b'!');
+        assert!(!verify(&key, AuthProtocol::HmacSha1, &message, &digest));
+    }
+
+    #[test]
+    fn test_des_encrypt_decrypt_roundtrip() {
+        let priv_key = vec![9u8; 16];
+        let plaintext = b"a ScopedPdu that is not block-aligned";
+        let (ciphertext, privacy_parameters) = encrypt_des(&priv_key, 3, 42, plaintext).unwrap();
+        let decrypted = decrypt_des(&priv_key, &privacy_parameters, &ciphertext).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_aes128_encrypt_decrypt_roundtrip() {
+        let priv_key = vec![5u8; 16];
+        let plaintext = b"a ScopedPdu, any length since CFB is a stream mode";
+        let (ciphertext, privacy_parameters) =
+            encrypt_aes128(&priv_key, 3, 1000, 7, plaintext).unwrap();
+        assert_eq!(ciphertext.len(), plaintext.len());
+        let decrypted =
+            decrypt_aes128(&priv_key, 3, 1000, &privacy_parameters, &ciphertext).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_security_parameters_roundtrip() {
+        let params = SecurityParameters {
+            authoritative_engine_id: vec![0x80, 0x00, 0x1f, 0x88, 0x80],
+            authoritative_engine_boots: 3,
+            authoritative_engine_time: 123_456,
+            user_name: "alice".to_string(),
+            authentication_parameters: vec![0u8; 12],
+            privacy_parameters: vec![0u8; 8],
+        };
+        let mut buf = Vec::new();
+        params.encode(&mut buf);
+        assert_eq!(SecurityParameters::decode(&buf).unwrap(), params);
+    }
+
+    #[test]
+    fn test_security_parameters_discovery_probe_is_all_empty() {
+        let probe = SecurityParameters::discovery_probe();
+        assert!(probe.authoritative_engine_id.is_empty());
+        assert_eq!(probe.authoritative_engine_boots, 0);
+        assert_eq!(probe.authoritative_engine_time, 0);
+        assert!(probe.user_name.is_empty());
+    }
+
+    #[test]
+    fn test_discovery_round_trip_from_agent_reply() {
+        let probe = SecurityParameters::encode_discovery_probe();
+        assert_eq!(
+            SecurityParameters::decode(&probe).unwrap(),
+            SecurityParameters::discovery_probe()
+        );
+
+        let mut reply = Vec::new();
+        SecurityParameters {
+            authoritative_engine_id: vec![0x80, 0x00, 0x1f, 0x88, 0x04],
+            authoritative_engine_boots: 3,
+            authoritative_engine_time: 12345,
+            ..Default::default()
+        }
+        .encode(&mut reply);
+
+        let (engine_id, engine_boots, engine_time) =
+            SecurityParameters::from_discovery_reply(&reply).unwrap();
+        assert_eq!(engine_id, vec![0x80, 0x00, 0x1f, 0x88, 0x04]);
+        assert_eq!(engine_boots, 3);
+        assert_eq!(engine_time, 12345);
+
+        let user = UsmUser {
+            name: "discovery-user".to_string(),
+            auth_protocol: None,
+            auth_key: Vec::new(),
+            priv_protocol: None,
+            priv_key: Vec::new(),
+        };
+        let session = UsmSession::from_discovery_reply(user, &reply).unwrap();
+        assert_eq!(session.engine_id, engine_id);
+        assert_eq!(session.engine_boots, engine_boots);
+        assert_eq!(session.engine_time, engine_time);
+    }
+
+    #[test]
+    fn test_scoped_pdu_roundtrip() {
+        let scoped = ScopedPdu {
+            context_engine_id: vec![1, 2, 3],
+            context_name: b"my-context".to_vec(),
+            pdu: vec![0x30, 0x03, 0x02, 0x01, 0x05],
+        };
+        let mut buf = Vec::new();
+        scoped.encode(&mut buf);
+        assert_eq!(ScopedPdu::decode(&buf).unwrap(), scoped);
+    }
+
+    #[test]
+    fn test_usm_session_authenticate_and_verify_roundtrip() {
+        let engine_id = b"discovered-engine".to_vec();
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacSha1);
+        let auth_key = localize_key(&ku, &engine_id, AuthProtocol::HmacSha1);
+        let user = UsmUser {
+            name: "alice".to_string(),
+            auth_protocol: Some(AuthProtocol::HmacSha1),
+            auth_key,
+            priv_protocol: None,
+            priv_key: Vec::new(),
+        };
+        let session = UsmSession::new(user, engine_id, 1, 100);
+
+        let message = b"a serialized SNMPv3 message with auth-params zeroed";
+        let digest = session.authenticate(message);
+        assert!(session.verify(message, &digest));
+        assert!(!session.verify(b"a tampered message, same length!!!", &digest));
+    }
+
+    #[test]
+    fn test_usm_session_encrypt_decrypt_roundtrip_never_reuses_privacy_parameters() {
+        let engine_id = b"discovered-engine".to_vec();
+        let ku = password_to_key(b"maplesyrup", AuthProtocol::HmacMd5);
+        let auth_key = localize_key(&ku, &engine_id, AuthProtocol::HmacMd5);
+        let priv_key = localize_key(&ku, &engine_id, AuthProtocol::HmacMd5);
+        let user = UsmUser {
+            name: "alice".to_string(),
+            auth_protocol: Some(AuthProtocol::HmacMd5),
+            auth_key,
+            priv_protocol: Some(PrivProtocol::Aes128),
+            priv_key,
+        };
+        let mut session = UsmSession::new(user, engine_id, 1, 100);
+
+        let (ciphertext_one, privacy_params_one) =
+            session.encrypt(b"scoped pdu one").unwrap().unwrap();
+        let (ciphertext_two, privacy_params_two) =
+            session.encrypt(b"scoped pdu two").unwrap().unwrap();
+        assert_ne!(privacy_params_one, privacy_params_two);
+
+        let plaintext_one = session
+            .decrypt(&privacy_params_one, &ciphertext_one)
+            .unwrap()
+            .unwrap();
+        let plaintext_two = session
+            .decrypt(&privacy_params_two, &ciphertext_two)
+            .unwrap()
+            .unwrap();
+        assert_eq!(plaintext_one, b"scoped pdu one");
+        assert_eq!(plaintext_two, b"scoped pdu two");
+    }
+}